@@ -0,0 +1,226 @@
+//! # Composing diffs
+//!
+//! [`Composable`] squashes the diff from `a` to `b` and the diff from `b` to `c` into a single diff
+//! from `a` to `c`, without needing any of the values. This allows compacting a history of diffs.
+//!
+//! ```rust
+//! use diffogus::compose::Composable;
+//! use diffogus::diff::Diffable;
+//!
+//! let (a, b, c) = (Some(1), None, Some(3));
+//! let diff = Composable::compose(a.diff(&b), b.diff(&c)).unwrap();
+//! assert_eq!(a.diff(&c), diff);
+//! ```
+//!
+//! Changes that cancel out collapse into `Unchanged`, and an item added by the first diff and
+//! removed by the second one disappears altogether. The second diff has to start where the first
+//! one ends, otherwise [`PatchError`] is returned, just like when applying a mismatched diff.
+
+use crate::diff::{
+    BTreeMapDiff, Changeable, CollectionDiffEntry, Diffable, HashMapDiff, OptionDiff,
+    PrimitiveDiff, VecDiff,
+};
+use crate::invert::Invertible;
+use crate::patch::{PatchError, Patchable};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// Trait for diff representations that can be squashed into one.
+pub trait Composable: Sized {
+    /// Combines `self`, the diff from `a` to `b`, with `then`, the diff from `b` to `c`, into the
+    /// diff from `a` to `c`.
+    fn compose(self, then: Self) -> Result<Self, PatchError>;
+}
+
+impl<T: Diffable> Composable for PrimitiveDiff<T> {
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        match (self, then) {
+            (Self::Unchanged, diff) | (diff, Self::Unchanged) => Ok(diff),
+            (Self::Changed { old, new: mid }, Self::Changed { old: then_mid, new }) => {
+                if mid.diff(&then_mid).is_changed() {
+                    return Err(PatchError::Mismatch);
+                }
+                if old.diff(&new).is_changed() {
+                    Ok(Self::Changed { old, new })
+                } else {
+                    Ok(Self::Unchanged)
+                }
+            }
+        }
+    }
+}
+
+/// Composes two changes to the same item. `None` means the item was added and removed again, so
+/// it doesn't appear in the result at all.
+fn compose_entry<T>(
+    first: CollectionDiffEntry<T>,
+    then: CollectionDiffEntry<T>,
+) -> Result<Option<CollectionDiffEntry<T>>, PatchError>
+where
+    T: Patchable,
+    <T as Diffable>::Repr: Composable + Invertible,
+{
+    use CollectionDiffEntry::*;
+
+    let entry = match (first, then) {
+        (Unchanged, entry) | (entry, Unchanged) => entry,
+        (Added(mut value), Changed(diff)) => {
+            value.apply(&diff)?;
+            Added(value)
+        }
+        (Added(new), Removed(old)) => {
+            if new.diff(&old).is_changed() {
+                return Err(PatchError::Mismatch);
+            }
+            return Ok(None);
+        }
+        (Removed(old), Added(new)) => match old.diff(&new) {
+            diff if diff.is_changed() => Changed(diff),
+            _ => Unchanged,
+        },
+        (Changed(first), Changed(then)) => match first.compose(then)? {
+            diff if diff.is_changed() => Changed(diff),
+            _ => Unchanged,
+        },
+        (Changed(diff), Removed(mut value)) => {
+            value.apply(&diff.invert())?;
+            Removed(value)
+        }
+        (Added(_) | Changed(_), Added(_)) => return Err(PatchError::Exists),
+        (Removed(_), Removed(_) | Changed(_)) => return Err(PatchError::Missing),
+    };
+    Ok(Some(entry))
+}
+
+impl<T> Composable for OptionDiff<T>
+where
+    T: Patchable,
+    <T as Diffable>::Repr: Composable + Invertible,
+{
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        fn entry<T: Diffable>(diff: OptionDiff<T>) -> CollectionDiffEntry<T> {
+            match diff {
+                OptionDiff::Removed(v) => CollectionDiffEntry::Removed(v),
+                OptionDiff::Added(v) => CollectionDiffEntry::Added(v),
+                OptionDiff::Changed(diff) => CollectionDiffEntry::Changed(diff),
+                OptionDiff::Unchanged => CollectionDiffEntry::Unchanged,
+            }
+        }
+
+        Ok(match compose_entry(entry(self), entry(then))? {
+            Some(CollectionDiffEntry::Removed(v)) => Self::Removed(v),
+            Some(CollectionDiffEntry::Added(v)) => Self::Added(v),
+            Some(CollectionDiffEntry::Changed(diff)) => Self::Changed(diff),
+            Some(CollectionDiffEntry::Unchanged) | None => Self::Unchanged,
+        })
+    }
+}
+
+impl<T> Composable for VecDiff<T>
+where
+    T: Patchable,
+    <T as Diffable>::Repr: Composable + Invertible,
+{
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        let mut first = self.0.into_iter().peekable();
+        let mut out = vec![];
+
+        for entry in then.0 {
+            if let CollectionDiffEntry::Added(_) = entry {
+                // An item added where one was removed replaces it.
+                match first.next_if(|prev| matches!(prev, CollectionDiffEntry::Removed(_))) {
+                    Some(removed) => out.extend(compose_entry(removed, entry)?),
+                    None => out.push(entry),
+                }
+                continue;
+            }
+
+            // Items removed by the first diff are gone before the second diff sees the vector.
+            let prev = loop {
+                match first.next().ok_or(PatchError::Missing)? {
+                    removed @ CollectionDiffEntry::Removed(_) => out.push(removed),
+                    prev => break prev,
+                }
+            };
+            out.extend(compose_entry(prev, entry)?);
+        }
+
+        for entry in first {
+            match entry {
+                CollectionDiffEntry::Removed(_) => out.push(entry),
+                _ => return Err(PatchError::Mismatch),
+            }
+        }
+        Ok(Self(out))
+    }
+}
+
+/// Composes the entries of two map diffs. Keys missing from one of the diffs count as unchanged.
+pub(crate) fn compose_map<K, T, M>(first: M, mut then: M) -> Result<M, PatchError>
+where
+    T: Patchable,
+    <T as Diffable>::Repr: Composable + Invertible,
+    M: MapEntries<K, T>,
+{
+    let mut out = M::default();
+    for (k, entry) in first {
+        let then = then.take(&k).unwrap_or_default();
+        if let Some(entry) = compose_entry(entry, then)? {
+            out.put(k, entry);
+        }
+    }
+    for (k, entry) in then {
+        out.put(k, entry);
+    }
+    Ok(out)
+}
+
+/// Maps of diff entries that can be composed by [`compose_map`].
+pub(crate) trait MapEntries<K, T: Diffable>:
+    Default + IntoIterator<Item = (K, CollectionDiffEntry<T>)>
+{
+    fn take(&mut self, k: &K) -> Option<CollectionDiffEntry<T>>;
+    fn put(&mut self, k: K, entry: CollectionDiffEntry<T>);
+}
+
+impl<K: Hash + Eq, T: Diffable> MapEntries<K, T> for HashMap<K, CollectionDiffEntry<T>> {
+    fn take(&mut self, k: &K) -> Option<CollectionDiffEntry<T>> {
+        self.remove(k)
+    }
+
+    fn put(&mut self, k: K, entry: CollectionDiffEntry<T>) {
+        self.insert(k, entry);
+    }
+}
+
+impl<K: Ord, T: Diffable> MapEntries<K, T> for BTreeMap<K, CollectionDiffEntry<T>> {
+    fn take(&mut self, k: &K) -> Option<CollectionDiffEntry<T>> {
+        self.remove(k)
+    }
+
+    fn put(&mut self, k: K, entry: CollectionDiffEntry<T>) {
+        self.insert(k, entry);
+    }
+}
+
+impl<K, T> Composable for HashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Patchable,
+    <T as Diffable>::Repr: Composable + Invertible,
+{
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        compose_map(self.0, then.0).map(Self)
+    }
+}
+
+impl<K, T> Composable for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Patchable,
+    <T as Diffable>::Repr: Composable + Invertible,
+{
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        compose_map(self.0, then.0).map(Self)
+    }
+}
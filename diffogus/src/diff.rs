@@ -2,9 +2,18 @@
 use serde::{Deserialize, Serialize};
 
 use crate::MySerialize;
-use std::collections::{BTreeMap, HashMap};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Debug;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem;
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Wrapping,
+};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::sync::Arc;
 
 /// Trait representing an object that can determine if it has changed.
 pub trait Changeable {
@@ -22,8 +31,150 @@ pub trait Diffable {
     /// The type used to represent the difference between two objects.
     type Repr: Changeable + Debug + for<'de> MySerialize<'de>;
 
-    /// Computes the difference between `self` and another object of the same type.
+    /// Computes the difference between `self` and another object of the same type, using the
+    /// default [`DiffOptions`].
     fn diff(&self, b: &Self) -> Self::Repr;
+
+    /// Computes the difference between `self` and `b`, configured by `options`.
+    ///
+    /// The default ignores `options` and calls [`Diffable::diff`]. Implementations that support
+    /// options override it, forward `options` to the values they contain and usually implement
+    /// [`Diffable::diff`] as `self.diff_with(b, &DiffOptions::default())`.
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        let _ = options;
+        self.diff(b)
+    }
+}
+
+/// Strategy used to line up the items of two `Vec`s or slices.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VecAlgorithm {
+    /// Compares the items at the same index, so an insertion changes every item after it.
+    #[default]
+    Positional,
+    /// Lines up equal items along the longest common subsequence, so insertions and removals are
+    /// reported as such. Items in between are paired up as changes. Takes quadratic time.
+    Lcs,
+}
+
+/// Options controlling how values are diffed, passed to [`Diffable::diff_with`].
+///
+/// ```rust
+/// use diffogus::diff::{Changeable, DiffOptions, Diffable, Tolerance};
+///
+/// let options = DiffOptions::new()
+///     .ignore_case(true)
+///     .tolerance(Tolerance::absolute(0.1));
+/// assert!(!"Hello".to_string().diff_with(&"hello".to_string(), &options).is_changed());
+/// assert!(!1.0_f64.diff_with(&1.05, &options).is_changed());
+/// ```
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct DiffOptions {
+    /// Tolerance used to compare floats. Floats within machine epsilon are equal when unset.
+    pub tolerance: Option<Tolerance>,
+    /// Whether strings are compared ignoring case.
+    pub ignore_case: bool,
+    /// Strategy used to line up the items of `Vec`s and slices.
+    pub vec_algorithm: VecAlgorithm,
+    /// Names of struct fields and JSON object keys that are reported as unchanged. Fields of
+    /// derived types are named as in their serialized diff.
+    pub ignore: BTreeSet<String>,
+    /// Whether `IndexMap`s and `IndexSet`s report keys present on both sides that changed order.
+    pub detect_reorder: bool,
+}
+
+impl DiffOptions {
+    /// Creates the default options, same as [`Diffable::diff`] uses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the tolerance used to compare floats.
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Sets whether strings are compared ignoring case.
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Sets the strategy used to line up the items of `Vec`s and slices.
+    pub fn vec_algorithm(mut self, vec_algorithm: VecAlgorithm) -> Self {
+        self.vec_algorithm = vec_algorithm;
+        self
+    }
+
+    /// Sets whether `IndexMap`s and `IndexSet`s report keys that changed order.
+    pub fn detect_reorder(mut self, detect_reorder: bool) -> Self {
+        self.detect_reorder = detect_reorder;
+        self
+    }
+
+    /// Reports struct fields and JSON object keys called `name` as unchanged.
+    pub fn ignore(mut self, name: impl Into<String>) -> Self {
+        self.ignore.insert(name.into());
+        self
+    }
+
+    /// Checks if struct fields and JSON object keys called `name` are ignored.
+    pub fn is_ignored(&self, name: &str) -> bool {
+        self.ignore.contains(name)
+    }
+
+    /// Checks if two strings are equal, ignoring case if configured to.
+    pub fn str_eq(&self, a: &str, b: &str) -> bool {
+        if self.ignore_case {
+            a.chars()
+                .flat_map(char::to_lowercase)
+                .eq(b.chars().flat_map(char::to_lowercase))
+        } else {
+            a == b
+        }
+    }
+}
+
+/// Tolerance used when comparing floating point values with [`ApproxDiffable`].
+///
+/// Two values `a` and `b` are considered equal when
+/// `|a - b| <= absolute + relative * max(|a|, |b|)`.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Largest absolute difference that is still considered unchanged.
+    pub absolute: f64,
+    /// Largest difference, relative to the larger of both magnitudes, that is still considered unchanged.
+    pub relative: f64,
+}
+
+impl Tolerance {
+    /// Creates a tolerance allowing an absolute difference of up to `absolute`.
+    pub const fn absolute(absolute: f64) -> Self {
+        Self {
+            absolute,
+            relative: 0.0,
+        }
+    }
+
+    /// Creates a tolerance allowing a relative difference of up to `relative`.
+    pub const fn relative(relative: f64) -> Self {
+        Self {
+            absolute: 0.0,
+            relative,
+        }
+    }
+
+    /// Checks if `a` and `b` are equal within this tolerance.
+    pub fn approx_eq(&self, a: f64, b: f64) -> bool {
+        a == b || (a - b).abs() <= self.absolute + self.relative * a.abs().max(b.abs())
+    }
+}
+
+/// Trait for types containing floating point values that can be diffed with a custom [`Tolerance`].
+pub trait ApproxDiffable: Diffable {
+    /// Computes the difference between `self` and `b`, treating floats within `tolerance` as unchanged.
+    fn diff_approx(&self, b: &Self, tolerance: &Tolerance) -> Self::Repr;
 }
 
 /// Enum representing the difference between two primitive values.
@@ -71,6 +222,36 @@ where
     }
 }
 
+impl<T> Eq for PrimitiveDiff<T> where T: Diffable + Eq {}
+
+impl<T> Clone for PrimitiveDiff<T>
+where
+    T: Diffable + Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Changed { old, new } => Self::Changed {
+                old: old.clone(),
+                new: new.clone(),
+            },
+            Self::Unchanged => Self::Unchanged,
+        }
+    }
+}
+
+impl<T> Hash for PrimitiveDiff<T>
+where
+    T: Diffable + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        if let Self::Changed { old, new } = self {
+            old.hash(state);
+            new.hash(state);
+        }
+    }
+}
+
 impl<T> Changeable for PrimitiveDiff<T>
 where
     T: Diffable,
@@ -101,7 +282,37 @@ macro_rules! impl_ints {
     };
 }
 
-impl_ints!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, bool);
+impl_ints!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    bool,
+    char,
+    ()
+);
+impl_ints!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
 
 /// Macro to implement the `Diffable` trait for floating point types.
 #[doc(hidden)]
@@ -111,7 +322,25 @@ macro_rules! impl_floats {
             type Repr = PrimitiveDiff<$ty>;
 
             fn diff(&self, b: &Self) -> Self::Repr {
-                if (b - self).abs() <= <$ty>::EPSILON {
+                self.diff_with(b, &DiffOptions::default())
+            }
+
+            fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+                let unchanged = match &options.tolerance {
+                    Some(tolerance) => tolerance.approx_eq(*self as f64, *b as f64),
+                    None => (b - self).abs() <= <$ty>::EPSILON,
+                };
+                if unchanged {
+                    PrimitiveDiff::Unchanged
+                } else {
+                    PrimitiveDiff::Changed { old: *self, new: *b }
+                }
+            }
+        }
+
+        impl ApproxDiffable for $ty {
+            fn diff_approx(&self, b: &Self, tolerance: &Tolerance) -> Self::Repr {
+                if tolerance.approx_eq(*self as f64, *b as f64) {
                     PrimitiveDiff::Unchanged
                 } else {
                     PrimitiveDiff::Changed { old: *self, new: *b }
@@ -130,7 +359,11 @@ impl Diffable for String {
     type Repr = PrimitiveDiff<String>;
 
     fn diff(&self, b: &Self) -> Self::Repr {
-        if self == b {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        if options.str_eq(self, b) {
             PrimitiveDiff::Unchanged
         } else {
             PrimitiveDiff::Changed {
@@ -141,6 +374,154 @@ impl Diffable for String {
     }
 }
 
+impl Diffable for str {
+    type Repr = PrimitiveDiff<String>;
+
+    fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        if options.str_eq(self, b) {
+            PrimitiveDiff::Unchanged
+        } else {
+            PrimitiveDiff::Changed {
+                old: self.to_owned(),
+                new: b.to_owned(),
+            }
+        }
+    }
+}
+
+/// Macro to implement the `Diffable` trait for owning string types, which diff like `str`.
+#[doc(hidden)]
+macro_rules! impl_strs {
+    ($($ty:ty),*) => {
+        $(
+            impl Diffable for $ty {
+                type Repr = PrimitiveDiff<String>;
+
+                fn diff(&self, b: &Self) -> Self::Repr {
+                    self.diff_with(b, &DiffOptions::default())
+                }
+
+                fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+                    (**self).diff_with(&**b, options)
+                }
+            }
+        )*
+    };
+}
+
+impl_strs!(Box<str>, Rc<str>, Arc<str>, Cow<'_, str>);
+
+/// `Wrapping` integers diff like the integer they wrap.
+impl<T> Diffable for Wrapping<T>
+where
+    T: Diffable,
+{
+    type Repr = T::Repr;
+
+    fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        self.0.diff_with(&b.0, options)
+    }
+}
+
+/// Shared references diff like the value they point to, so their diffs don't borrow from the inputs.
+impl<T> Diffable for &T
+where
+    T: Diffable + ?Sized,
+{
+    type Repr = T::Repr;
+
+    fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        (**self).diff_with(*b, options)
+    }
+}
+
+impl<T> ApproxDiffable for &T
+where
+    T: ApproxDiffable + ?Sized,
+{
+    fn diff_approx(&self, b: &Self, tolerance: &Tolerance) -> Self::Repr {
+        (**self).diff_approx(*b, tolerance)
+    }
+}
+
+/// Wrapper that diffs the inner value as a whole, using `PartialEq` instead of [`Diffable`].
+///
+/// Useful for types that have no meaningful structural diff. Any difference between the two
+/// values is reported as [`PrimitiveDiff::Changed`] holding both of them.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Atomic<T>(pub T);
+
+impl<T> Atomic<T> {
+    /// Unwraps the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Atomic<T>
+where
+    T: PartialEq + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    /// Computes the difference between two unwrapped values.
+    pub fn diff_values(a: &T, b: &T) -> PrimitiveDiff<Self> {
+        if a == b {
+            PrimitiveDiff::Unchanged
+        } else {
+            PrimitiveDiff::Changed {
+                old: Atomic(a.clone()),
+                new: Atomic(b.clone()),
+            }
+        }
+    }
+}
+
+impl<T> From<T> for Atomic<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for Atomic<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Atomic<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> Diffable for Atomic<T>
+where
+    T: PartialEq + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    type Repr = PrimitiveDiff<Self>;
+
+    fn diff(&self, b: &Self) -> Self::Repr {
+        Self::diff_values(&self.0, &b.0)
+    }
+}
+
 /// Enum representing a difference in collections such as `HashMap` or `Vec`.
 #[derive(Default, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -176,6 +557,43 @@ where
     }
 }
 
+impl<T> Eq for CollectionDiffEntry<T>
+where
+    T: Diffable + Eq,
+    <T as Diffable>::Repr: Eq,
+{
+}
+
+impl<T> Clone for CollectionDiffEntry<T>
+where
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Removed(v) => Self::Removed(v.clone()),
+            Self::Added(v) => Self::Added(v.clone()),
+            Self::Changed(diff) => Self::Changed(diff.clone()),
+            Self::Unchanged => Self::Unchanged,
+        }
+    }
+}
+
+impl<T> Hash for CollectionDiffEntry<T>
+where
+    T: Diffable + Hash,
+    <T as Diffable>::Repr: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Self::Removed(v) | Self::Added(v) => v.hash(state),
+            Self::Changed(diff) => diff.hash(state),
+            Self::Unchanged => {}
+        }
+    }
+}
+
 impl<T: Diffable> Changeable for CollectionDiffEntry<T> {
     fn is_changed(&self) -> bool {
         !matches!(self, Self::Unchanged)
@@ -183,13 +601,53 @@ impl<T: Diffable> Changeable for CollectionDiffEntry<T> {
 }
 
 /// Represents the difference between two `HashMap` collections.
-#[derive(Default, Debug)]
+#[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct HashMapDiff<K, T>(pub HashMap<K, CollectionDiffEntry<T>>)
 where
     K: Hash + Eq,
     T: Diffable;
 
+impl<K, T> Default for HashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+{
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<K, T> PartialEq for HashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable + PartialEq,
+    <T as Diffable>::Repr: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K, T> Eq for HashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable + Eq,
+    <T as Diffable>::Repr: Eq,
+{
+}
+
+impl<K, T> Clone for HashMapDiff<K, T>
+where
+    K: Hash + Eq + Clone,
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
 impl<K, T> Changeable for HashMapDiff<K, T>
 where
     K: Hash + Eq,
@@ -200,23 +658,28 @@ where
     }
 }
 
-impl<K, T> Diffable for HashMap<K, T>
+impl<K, T, S> Diffable for HashMap<K, T, S>
 where
     K: Hash + Eq + Debug + Clone,
     T: Diffable + Debug + Clone,
     for<'de> T: MySerialize<'de>,
     for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
 {
     type Repr = HashMapDiff<K, T>;
 
     fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
         let mut out = HashMap::new();
 
         for (k, v) in self {
             let other = b.get(k);
             match other {
                 Some(other) => {
-                    let diff = v.diff(other);
+                    let diff = v.diff_with(other, options);
                     if diff.is_changed() {
                         out.insert(k.clone(), CollectionDiffEntry::Changed(diff))
                     } else {
@@ -239,13 +702,64 @@ where
 }
 
 /// Represents the difference between two `HashMap` collections.
-#[derive(Default, Debug)]
+#[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BTreeMapDiff<K, T>(pub BTreeMap<K, CollectionDiffEntry<T>>)
 where
     K: Hash + Eq + Ord,
     T: Diffable;
 
+impl<K, T> Default for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable,
+{
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
+
+impl<K, T> PartialEq for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable + PartialEq,
+    <T as Diffable>::Repr: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K, T> Eq for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable + Eq,
+    <T as Diffable>::Repr: Eq,
+{
+}
+
+impl<K, T> Clone for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord + Clone,
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K, T> Hash for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable + Hash,
+    <T as Diffable>::Repr: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
 impl<K, T> Changeable for BTreeMapDiff<K, T>
 where
     K: Hash + Eq + Ord,
@@ -266,13 +780,17 @@ where
     type Repr = BTreeMapDiff<K, T>;
 
     fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
         let mut out = BTreeMap::new();
 
         for (k, v) in self {
             let other = b.get(k);
             match other {
                 Some(other) => {
-                    let diff = v.diff(other);
+                    let diff = v.diff_with(other, options);
                     if diff.is_changed() {
                         out.insert(k.clone(), CollectionDiffEntry::Changed(diff))
                     } else {
@@ -295,10 +813,16 @@ where
 }
 
 /// Represents the difference between two `Vec` collections.
-#[derive(Default, Debug)]
+#[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct VecDiff<T: Diffable>(pub Vec<CollectionDiffEntry<T>>);
 
+impl<T: Diffable> Default for VecDiff<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
 impl<'de, T> PartialEq for VecDiff<T>
 where
     T: Diffable + PartialEq + MySerialize<'de>,
@@ -309,6 +833,33 @@ where
     }
 }
 
+impl<'de, T> Eq for VecDiff<T>
+where
+    T: Diffable + Eq + MySerialize<'de>,
+    <T as Diffable>::Repr: Eq + MySerialize<'de>,
+{
+}
+
+impl<T> Clone for VecDiff<T>
+where
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Hash for VecDiff<T>
+where
+    T: Diffable + Hash,
+    <T as Diffable>::Repr: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
 impl<T> Changeable for VecDiff<T>
 where
     T: Diffable + PartialEq,
@@ -318,6 +869,119 @@ where
     }
 }
 
+/// Step lining up the items of two slices, see [`align`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Step {
+    /// `a[i]` and `b[j]` are known to be equal.
+    Equal(usize, usize),
+    /// `a[i]` and `b[j]` are at the same position and have to be diffed.
+    Pair(usize, usize),
+    /// `a[i]` was removed.
+    Removed(usize),
+    /// `b[j]` was added.
+    Added(usize),
+}
+
+/// Lines up the items of `a` and `b` with `algorithm`, using `eq` to find equal items.
+pub(crate) fn align<T>(
+    a: &[T],
+    b: &[T],
+    algorithm: VecAlgorithm,
+    eq: impl Fn(&T, &T) -> bool,
+) -> Vec<Step> {
+    if algorithm == VecAlgorithm::Lcs {
+        return align_lcs(a, b, eq);
+    }
+
+    let paired = a.len().min(b.len());
+    (0..paired)
+        .map(|i| Step::Pair(i, i))
+        .chain((paired..a.len()).map(Step::Removed))
+        .chain((paired..b.len()).map(Step::Added))
+        .collect()
+}
+
+fn align_lcs<T>(a: &[T], b: &[T], eq: impl Fn(&T, &T) -> bool) -> Vec<Step> {
+    let width = b.len() + 1;
+    let equal: Vec<bool> = a.iter().flat_map(|a| b.iter().map(|b| eq(a, b))).collect();
+    // `lcs[i * width + j]` is the length of the longest common subsequence of `a[i..]` and `b[j..]`.
+    let mut lcs = vec![0usize; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if equal[i * b.len() + j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut steps = vec![];
+    let (mut removed, mut added) = (vec![], vec![]);
+    // Items between two matches are paired up as changes, the rest are removed or added.
+    let flush = |removed: &mut Vec<usize>, added: &mut Vec<usize>, steps: &mut Vec<Step>| {
+        let paired = removed.len().min(added.len());
+        steps.extend(
+            removed
+                .iter()
+                .zip(added.iter())
+                .map(|(&i, &j)| Step::Pair(i, j)),
+        );
+        steps.extend(removed.drain(..).skip(paired).map(Step::Removed));
+        steps.extend(added.drain(..).skip(paired).map(Step::Added));
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && equal[i * b.len() + j] {
+            flush(&mut removed, &mut added, &mut steps);
+            steps.push(Step::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if j == b.len()
+            || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            removed.push(i);
+            i += 1;
+        } else {
+            added.push(j);
+            j += 1;
+        }
+    }
+    flush(&mut removed, &mut added, &mut steps);
+
+    steps
+}
+
+fn diff_vec_by<T>(
+    a: &[T],
+    b: &[T],
+    algorithm: VecAlgorithm,
+    diff: impl Fn(&T, &T) -> T::Repr,
+) -> VecDiff<T>
+where
+    T: Diffable + Clone,
+{
+    let entries = align(a, b, algorithm, |a, b| !diff(a, b).is_changed())
+        .into_iter()
+        .map(|step| match step {
+            Step::Equal(..) => CollectionDiffEntry::Unchanged,
+            Step::Pair(i, j) => {
+                let diff = diff(&a[i], &b[j]);
+                if diff.is_changed() {
+                    CollectionDiffEntry::Changed(diff)
+                } else {
+                    CollectionDiffEntry::Unchanged
+                }
+            }
+            Step::Removed(i) => CollectionDiffEntry::Removed(a[i].clone()),
+            Step::Added(j) => CollectionDiffEntry::Added(b[j].clone()),
+        })
+        .collect();
+
+    VecDiff(entries)
+}
+
 impl<T> Diffable for Vec<T>
 where
     T: Diffable + Debug + Clone + PartialEq,
@@ -326,30 +990,55 @@ where
     type Repr = VecDiff<T>;
 
     fn diff(&self, b: &Self) -> Self::Repr {
-        let mut out = vec![];
+        self.diff_with(b, &DiffOptions::default())
+    }
 
-        let len = self.len().max(b.len());
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        diff_vec_by(self, b, options.vec_algorithm, |a, b| {
+            a.diff_with(b, options)
+        })
+    }
+}
 
-        for i in 0..len {
-            let old = self.get(i);
-            let new = b.get(i);
+impl<T> ApproxDiffable for Vec<T>
+where
+    T: ApproxDiffable + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    fn diff_approx(&self, b: &Self, tolerance: &Tolerance) -> Self::Repr {
+        diff_vec_by(self, b, VecAlgorithm::Positional, |a, b| {
+            a.diff_approx(b, tolerance)
+        })
+    }
+}
 
-            match (old, new) {
-                (Some(a), None) => out.push(CollectionDiffEntry::Removed(a.clone())),
-                (Some(a), Some(b)) => {
-                    let diff = a.diff(b);
-                    if diff.is_changed() {
-                        out.push(CollectionDiffEntry::Changed(diff))
-                    } else {
-                        out.push(CollectionDiffEntry::Unchanged)
-                    }
-                }
-                (None, None) => out.push(CollectionDiffEntry::Unchanged),
-                (None, Some(b)) => out.push(CollectionDiffEntry::Added(b.clone())),
-            }
-        }
+impl<T> Diffable for [T]
+where
+    T: Diffable + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    type Repr = VecDiff<T>;
+
+    fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
 
-        VecDiff(out)
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        diff_vec_by(self, b, options.vec_algorithm, |a, b| {
+            a.diff_with(b, options)
+        })
+    }
+}
+
+impl<T> ApproxDiffable for [T]
+where
+    T: ApproxDiffable + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    fn diff_approx(&self, b: &Self, tolerance: &Tolerance) -> Self::Repr {
+        diff_vec_by(self, b, VecAlgorithm::Positional, |a, b| {
+            a.diff_approx(b, tolerance)
+        })
     }
 }
 
@@ -388,12 +1077,72 @@ where
     }
 }
 
+impl<T> Eq for OptionDiff<T>
+where
+    T: Diffable + Eq,
+    <T as Diffable>::Repr: Eq,
+{
+}
+
+impl<T> Clone for OptionDiff<T>
+where
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Removed(v) => Self::Removed(v.clone()),
+            Self::Added(v) => Self::Added(v.clone()),
+            Self::Changed(diff) => Self::Changed(diff.clone()),
+            Self::Unchanged => Self::Unchanged,
+        }
+    }
+}
+
+impl<T> Hash for OptionDiff<T>
+where
+    T: Diffable + Hash,
+    <T as Diffable>::Repr: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Self::Removed(v) | Self::Added(v) => v.hash(state),
+            Self::Changed(diff) => diff.hash(state),
+            Self::Unchanged => {}
+        }
+    }
+}
+
 impl<T: Diffable> Changeable for OptionDiff<T> {
     fn is_changed(&self) -> bool {
         !matches!(self, Self::Unchanged)
     }
 }
 
+fn diff_option_by<T>(
+    a: &Option<T>,
+    b: &Option<T>,
+    diff: impl Fn(&T, &T) -> T::Repr,
+) -> OptionDiff<T>
+where
+    T: Diffable + Clone,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let diffed = diff(a, b);
+            if diffed.is_changed() {
+                OptionDiff::Changed(diffed)
+            } else {
+                OptionDiff::Unchanged
+            }
+        }
+        (Some(a), None) => OptionDiff::Removed(a.clone()),
+        (None, Some(a)) => OptionDiff::Added(a.clone()),
+        (None, None) => OptionDiff::Unchanged,
+    }
+}
+
 impl<T> Diffable for Option<T>
 where
     T: Diffable + Clone + Debug,
@@ -402,18 +1151,20 @@ where
     type Repr = OptionDiff<T>;
 
     fn diff(&self, b: &Self) -> Self::Repr {
-        match (self, b) {
-            (Some(a), Some(b)) => {
-                let diffed = a.diff(b);
-                if diffed.is_changed() {
-                    OptionDiff::Changed(diffed)
-                } else {
-                    OptionDiff::Unchanged
-                }
-            }
-            (Some(a), None) => OptionDiff::Removed(a.clone()),
-            (None, Some(a)) => OptionDiff::Added(a.clone()),
-            (None, None) => OptionDiff::Unchanged,
-        }
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        diff_option_by(self, b, |a, b| a.diff_with(b, options))
+    }
+}
+
+impl<T> ApproxDiffable for Option<T>
+where
+    T: ApproxDiffable + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    fn diff_approx(&self, b: &Self, tolerance: &Tolerance) -> Self::Repr {
+        diff_option_by(self, b, |a, b| a.diff_approx(b, tolerance))
     }
 }
@@ -0,0 +1,590 @@
+//! # Borrowed diffs
+//!
+//! [`Diffable::diff`] clones every value it reports, which adds up for large maps and `Vec`s
+//! that are only inspected and thrown away. [`DiffableRef::diff_ref`] computes the same diff but
+//! borrows the changed values from the inputs instead. Call [`ToOwnedDiff::to_owned`] to turn it
+//! into the owned [`Diffable::Repr`] when it has to outlive them.
+//!
+//! ```rust
+//! use diffogus::diff::{Changeable, Diffable};
+//! use diffogus::diff_ref::{CollectionDiffEntryRef, DiffableRef, ToOwnedDiff};
+//! use std::collections::HashMap;
+//!
+//! let a = HashMap::from([("a".to_string(), vec![1, 2]), ("b".to_string(), vec![3])]);
+//! let b = HashMap::from([("a".to_string(), vec![1, 2])]);
+//!
+//! let diff = a.diff_ref(&b);
+//! assert!(diff.is_changed());
+//! assert!(matches!(diff.0[&"b".to_string()], CollectionDiffEntryRef::Removed(v) if v == &[3]));
+//! assert_eq!(a.diff(&b), diff.to_owned());
+//! ```
+//!
+//! Borrowed diffs serialize exactly like their owned counterparts, but can't be deserialized.
+//! Types deriving `Diff` don't implement [`DiffableRef`]; diff their large fields with
+//! [`DiffableRef::diff_ref`] directly instead.
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::diff::{
+    align, Atomic, BTreeMapDiff, Changeable, CollectionDiffEntry, DiffOptions, Diffable,
+    HashMapDiff, OptionDiff, PrimitiveDiff, Step, VecDiff,
+};
+use crate::{MySerialize, MySerializeRef};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hash};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Wrapping,
+};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Trait for borrowed diffs that can be turned into an owned diff.
+pub trait ToOwnedDiff {
+    /// The owned diff type.
+    type Owned;
+
+    /// Clones the borrowed values into an owned diff.
+    fn to_owned(&self) -> Self::Owned;
+}
+
+/// Trait for types that can compute a difference borrowing from both objects.
+pub trait DiffableRef: Diffable {
+    /// The type used to represent the difference, borrowing from both objects for `'a`.
+    type ReprRef<'a>: Changeable + Debug + MySerializeRef + ToOwnedDiff<Owned = Self::Repr>
+    where
+        Self: 'a;
+
+    /// Computes the difference between `self` and `b` without cloning, using the default
+    /// [`DiffOptions`].
+    fn diff_ref<'a>(&'a self, b: &'a Self) -> Self::ReprRef<'a> {
+        self.diff_ref_with(b, &DiffOptions::default())
+    }
+
+    /// Computes the difference between `self` and `b` without cloning, configured by `options`.
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a>;
+}
+
+/// Borrowed counterpart of [`PrimitiveDiff`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "lowercase", tag = "type", content = "value")
+)]
+pub enum PrimitiveDiffRef<'a, T: ?Sized> {
+    /// Indicates that the value has changed, borrowing the old and new values.
+    Changed {
+        /// Field holding the old value.
+        old: &'a T,
+        /// Field holding the new value.
+        new: &'a T,
+    },
+    /// Indicates that the value has not changed.
+    Unchanged,
+}
+
+impl<T: ?Sized> Changeable for PrimitiveDiffRef<'_, T> {
+    fn is_changed(&self) -> bool {
+        !matches!(self, Self::Unchanged)
+    }
+}
+
+impl<T> ToOwnedDiff for PrimitiveDiffRef<'_, T>
+where
+    T: ToOwned + ?Sized,
+    T::Owned: Diffable,
+{
+    type Owned = PrimitiveDiff<T::Owned>;
+
+    fn to_owned(&self) -> Self::Owned {
+        match self {
+            Self::Changed { old, new } => PrimitiveDiff::Changed {
+                old: (*old).to_owned(),
+                new: (*new).to_owned(),
+            },
+            Self::Unchanged => PrimitiveDiff::Unchanged,
+        }
+    }
+}
+
+/// Borrows both values if `changed`.
+fn primitive<'a, T: ?Sized>(a: &'a T, b: &'a T, changed: bool) -> PrimitiveDiffRef<'a, T> {
+    if changed {
+        PrimitiveDiffRef::Changed { old: a, new: b }
+    } else {
+        PrimitiveDiffRef::Unchanged
+    }
+}
+
+/// Macro to implement the `DiffableRef` trait for copyable primitives, which are cheap to diff.
+#[doc(hidden)]
+macro_rules! impl_primitives {
+    ($($ty:ty),*) => {
+        $(
+            impl DiffableRef for $ty {
+                type ReprRef<'a> = PrimitiveDiffRef<'a, $ty>;
+
+                fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+                    primitive(self, b, self.diff_with(b, options).is_changed())
+                }
+            }
+        )*
+    };
+}
+
+impl_primitives!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    bool,
+    char,
+    (),
+    f32,
+    f64
+);
+impl_primitives!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+
+impl DiffableRef for String {
+    type ReprRef<'a> = PrimitiveDiffRef<'a, String>;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        primitive(self, b, !options.str_eq(self, b))
+    }
+}
+
+impl DiffableRef for str {
+    type ReprRef<'a> = PrimitiveDiffRef<'a, str>;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        primitive(self, b, !options.str_eq(self, b))
+    }
+}
+
+/// Macro to implement the `DiffableRef` trait for owning string types, borrowing their `str`.
+#[doc(hidden)]
+macro_rules! impl_strs {
+    ($($ty:ty),*) => {
+        $(
+            impl DiffableRef for $ty {
+                type ReprRef<'a>
+                    = PrimitiveDiffRef<'a, str>
+                where
+                    Self: 'a;
+
+                fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+                    (**self).diff_ref_with(&**b, options)
+                }
+            }
+        )*
+    };
+}
+
+impl_strs!(Box<str>, Rc<str>, Arc<str>, Cow<'_, str>);
+
+impl<T> DiffableRef for Wrapping<T>
+where
+    T: DiffableRef,
+{
+    type ReprRef<'a>
+        = T::ReprRef<'a>
+    where
+        Self: 'a;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        self.0.diff_ref_with(&b.0, options)
+    }
+}
+
+impl<T> DiffableRef for &T
+where
+    T: DiffableRef + ?Sized,
+{
+    type ReprRef<'a>
+        = T::ReprRef<'a>
+    where
+        Self: 'a;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        (**self).diff_ref_with(*b, options)
+    }
+}
+
+impl<T> DiffableRef for Atomic<T>
+where
+    T: PartialEq + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    type ReprRef<'a>
+        = PrimitiveDiffRef<'a, Self>
+    where
+        Self: 'a;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, _options: &DiffOptions) -> Self::ReprRef<'a> {
+        primitive(self, b, self != b)
+    }
+}
+
+/// Borrowed counterpart of [`CollectionDiffEntry`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "lowercase", tag = "type", content = "value")
+)]
+pub enum CollectionDiffEntryRef<'a, T: DiffableRef + 'a> {
+    /// Indicates that an item was removed from the collection.
+    Removed(&'a T),
+    /// Indicates that an item was added to the collection.
+    Added(&'a T),
+    /// Indicates that an item has changed.
+    Changed(T::ReprRef<'a>),
+    /// Indicates that an item has not changed.
+    Unchanged,
+}
+
+impl<'a, T: DiffableRef + 'a> Changeable for CollectionDiffEntryRef<'a, T> {
+    fn is_changed(&self) -> bool {
+        !matches!(self, Self::Unchanged)
+    }
+}
+
+impl<'a, T> ToOwnedDiff for CollectionDiffEntryRef<'a, T>
+where
+    T: DiffableRef + Clone + 'a,
+{
+    type Owned = CollectionDiffEntry<T>;
+
+    fn to_owned(&self) -> Self::Owned {
+        match self {
+            Self::Removed(v) => CollectionDiffEntry::Removed((*v).clone()),
+            Self::Added(v) => CollectionDiffEntry::Added((*v).clone()),
+            Self::Changed(diff) => CollectionDiffEntry::Changed(diff.to_owned()),
+            Self::Unchanged => CollectionDiffEntry::Unchanged,
+        }
+    }
+}
+
+/// Borrowed counterpart of [`OptionDiff`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "lowercase", tag = "type", content = "value")
+)]
+pub enum OptionDiffRef<'a, T: DiffableRef + 'a> {
+    /// Indicates that a value was removed (i.e., `Some` became `None`).
+    Removed(&'a T),
+    /// Indicates that a value was added (i.e., `None` became `Some`).
+    Added(&'a T),
+    /// Indicates that the inner value of `Some` has changed.
+    Changed(T::ReprRef<'a>),
+    /// Indicates that the value has not changed.
+    Unchanged,
+}
+
+impl<'a, T: DiffableRef + 'a> Changeable for OptionDiffRef<'a, T> {
+    fn is_changed(&self) -> bool {
+        !matches!(self, Self::Unchanged)
+    }
+}
+
+impl<'a, T> ToOwnedDiff for OptionDiffRef<'a, T>
+where
+    T: DiffableRef + Clone + 'a,
+{
+    type Owned = OptionDiff<T>;
+
+    fn to_owned(&self) -> Self::Owned {
+        match self {
+            Self::Removed(v) => OptionDiff::Removed((*v).clone()),
+            Self::Added(v) => OptionDiff::Added((*v).clone()),
+            Self::Changed(diff) => OptionDiff::Changed(diff.to_owned()),
+            Self::Unchanged => OptionDiff::Unchanged,
+        }
+    }
+}
+
+impl<T> DiffableRef for Option<T>
+where
+    T: DiffableRef + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    type ReprRef<'a>
+        = OptionDiffRef<'a, T>
+    where
+        Self: 'a;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        match (self, b) {
+            (Some(a), Some(b)) => {
+                let diff = a.diff_ref_with(b, options);
+                if diff.is_changed() {
+                    OptionDiffRef::Changed(diff)
+                } else {
+                    OptionDiffRef::Unchanged
+                }
+            }
+            (Some(a), None) => OptionDiffRef::Removed(a),
+            (None, Some(b)) => OptionDiffRef::Added(b),
+            (None, None) => OptionDiffRef::Unchanged,
+        }
+    }
+}
+
+/// Borrowed counterpart of [`VecDiff`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct VecDiffRef<'a, T: DiffableRef + 'a>(pub Vec<CollectionDiffEntryRef<'a, T>>);
+
+impl<'a, T: DiffableRef + 'a> Changeable for VecDiffRef<'a, T> {
+    fn is_changed(&self) -> bool {
+        self.0.iter().any(|d| d.is_changed())
+    }
+}
+
+impl<'a, T> ToOwnedDiff for VecDiffRef<'a, T>
+where
+    T: DiffableRef + Clone + 'a,
+{
+    type Owned = VecDiff<T>;
+
+    fn to_owned(&self) -> Self::Owned {
+        VecDiff(self.0.iter().map(ToOwnedDiff::to_owned).collect())
+    }
+}
+
+/// Diffs two slices like [`Diffable`] does for `Vec`s, borrowing the items.
+fn diff_slices<'a, T>(a: &'a [T], b: &'a [T], options: &DiffOptions) -> VecDiffRef<'a, T>
+where
+    T: DiffableRef,
+{
+    let entries = align(a, b, options.vec_algorithm, |a, b| {
+        !a.diff_ref_with(b, options).is_changed()
+    })
+    .into_iter()
+    .map(|step| match step {
+        Step::Equal(..) => CollectionDiffEntryRef::Unchanged,
+        Step::Pair(i, j) => {
+            let diff = a[i].diff_ref_with(&b[j], options);
+            if diff.is_changed() {
+                CollectionDiffEntryRef::Changed(diff)
+            } else {
+                CollectionDiffEntryRef::Unchanged
+            }
+        }
+        Step::Removed(i) => CollectionDiffEntryRef::Removed(&a[i]),
+        Step::Added(j) => CollectionDiffEntryRef::Added(&b[j]),
+    })
+    .collect();
+
+    VecDiffRef(entries)
+}
+
+impl<T> DiffableRef for Vec<T>
+where
+    T: DiffableRef + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    type ReprRef<'a>
+        = VecDiffRef<'a, T>
+    where
+        Self: 'a;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        diff_slices(self, b, options)
+    }
+}
+
+impl<T> DiffableRef for [T]
+where
+    T: DiffableRef + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    type ReprRef<'a>
+        = VecDiffRef<'a, T>
+    where
+        Self: 'a;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        diff_slices(self, b, options)
+    }
+}
+
+/// Borrowed counterpart of [`HashMapDiff`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct HashMapDiffRef<'a, K, T>(pub HashMap<&'a K, CollectionDiffEntryRef<'a, T>>)
+where
+    K: Hash + Eq,
+    T: DiffableRef + 'a;
+
+impl<'a, K, T> Changeable for HashMapDiffRef<'a, K, T>
+where
+    K: Hash + Eq,
+    T: DiffableRef + 'a,
+{
+    fn is_changed(&self) -> bool {
+        self.0.values().any(|v| v.is_changed())
+    }
+}
+
+impl<'a, K, T> ToOwnedDiff for HashMapDiffRef<'a, K, T>
+where
+    K: Hash + Eq + Clone,
+    T: DiffableRef + Clone + 'a,
+{
+    type Owned = HashMapDiff<K, T>;
+
+    fn to_owned(&self) -> Self::Owned {
+        HashMapDiff(
+            self.0
+                .iter()
+                .map(|(k, entry)| ((*k).clone(), entry.to_owned()))
+                .collect(),
+        )
+    }
+}
+
+impl<K, T, S> DiffableRef for HashMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: DiffableRef + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    type ReprRef<'a>
+        = HashMapDiffRef<'a, K, T>
+    where
+        Self: 'a;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        let mut out = HashMap::new();
+
+        for (k, v) in self {
+            let other = b.get(k);
+            match other {
+                Some(other) => {
+                    let diff = v.diff_ref_with(other, options);
+                    if diff.is_changed() {
+                        out.insert(k, CollectionDiffEntryRef::Changed(diff))
+                    } else {
+                        out.insert(k, CollectionDiffEntryRef::Unchanged)
+                    }
+                }
+                None => out.insert(k, CollectionDiffEntryRef::Removed(v)),
+            };
+        }
+
+        for (k, v) in b {
+            if out.contains_key(k) {
+                continue;
+            }
+            out.insert(k, CollectionDiffEntryRef::Added(v));
+        }
+
+        HashMapDiffRef(out)
+    }
+}
+
+/// Borrowed counterpart of [`BTreeMapDiff`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BTreeMapDiffRef<'a, K, T>(pub BTreeMap<&'a K, CollectionDiffEntryRef<'a, T>>)
+where
+    K: Hash + Eq + Ord,
+    T: DiffableRef + 'a;
+
+impl<'a, K, T> Changeable for BTreeMapDiffRef<'a, K, T>
+where
+    K: Hash + Eq + Ord,
+    T: DiffableRef + 'a,
+{
+    fn is_changed(&self) -> bool {
+        self.0.values().any(|v| v.is_changed())
+    }
+}
+
+impl<'a, K, T> ToOwnedDiff for BTreeMapDiffRef<'a, K, T>
+where
+    K: Hash + Eq + Ord + Clone,
+    T: DiffableRef + Clone + 'a,
+{
+    type Owned = BTreeMapDiff<K, T>;
+
+    fn to_owned(&self) -> Self::Owned {
+        BTreeMapDiff(
+            self.0
+                .iter()
+                .map(|(k, entry)| ((*k).clone(), entry.to_owned()))
+                .collect(),
+        )
+    }
+}
+
+impl<K, T> DiffableRef for BTreeMap<K, T>
+where
+    K: Hash + Eq + Ord + Debug + Clone,
+    T: DiffableRef + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+{
+    type ReprRef<'a>
+        = BTreeMapDiffRef<'a, K, T>
+    where
+        Self: 'a;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        let mut out = BTreeMap::new();
+
+        for (k, v) in self {
+            let other = b.get(k);
+            match other {
+                Some(other) => {
+                    let diff = v.diff_ref_with(other, options);
+                    if diff.is_changed() {
+                        out.insert(k, CollectionDiffEntryRef::Changed(diff))
+                    } else {
+                        out.insert(k, CollectionDiffEntryRef::Unchanged)
+                    }
+                }
+                None => out.insert(k, CollectionDiffEntryRef::Removed(v)),
+            };
+        }
+
+        for (k, v) in b {
+            if out.contains_key(k) {
+                continue;
+            }
+            out.insert(k, CollectionDiffEntryRef::Added(v));
+        }
+
+        BTreeMapDiffRef(out)
+    }
+}
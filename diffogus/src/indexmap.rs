@@ -0,0 +1,616 @@
+//! # Diffing implementation for [`IndexMap`](::indexmap::IndexMap) and [`IndexSet`](::indexmap::IndexSet)
+//!
+//! Entries are diffed by key like for `HashMap`s. The order of the keys is ignored unless
+//! [`DiffOptions::detect_reorder`](crate::diff::DiffOptions::detect_reorder) is set, in which
+//! case keys present on both sides that changed their relative order are recorded as a
+//! [`KeyOrder`](crate::indexmap::KeyOrder).
+//!
+//! ```rust
+//! use diffogus::diff::{Changeable, DiffOptions, Diffable};
+//! use diffogus::indexmap::KeyOrder;
+//! use diffogus::patch::Patchable;
+//! use indexmap::IndexMap;
+//!
+//! let a = IndexMap::from([('a', 1), ('b', 2), ('c', 3)]);
+//! let b = IndexMap::from([('c', 3), ('a', 1), ('b', 2)]);
+//! assert!(!a.diff(&b).is_changed());
+//!
+//! let diff = a.diff_with(&b, &DiffOptions::new().detect_reorder(true));
+//! assert_eq!(
+//!     Some(KeyOrder { old: vec!['a', 'b', 'c'], new: vec!['c', 'a', 'b'] }),
+//!     diff.order
+//! );
+//!
+//! let mut patched = a.clone();
+//! patched.apply(&diff).unwrap();
+//! assert!(patched.keys().eq(b.keys()));
+//! ```
+//!
+//! Removed keys are taken out without moving the others and added keys are appended, so only
+//! the relative order of the keys present on both sides is recorded. Merging ignores key order.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::compose::{compose_map, Composable, MapEntries};
+use crate::diff::{Changeable, CollectionDiffEntry, DiffOptions, Diffable};
+use crate::invert::Invertible;
+use crate::merge::{Mergeable, Merger};
+use crate::patch::{PatchError, Patchable};
+use crate::path::PathSegment;
+use crate::rebase::{rebase_map, Rebasable, Rebaser, RejectReason};
+use crate::similarity::{distance_of, key_pairs, similarity_of, Similar};
+use crate::visit::{walk_map, Change, Walkable, Walker};
+use crate::MySerialize;
+use ::indexmap::{IndexMap, IndexSet};
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hash};
+
+/// Keys present on both sides of a diff, in their old and new order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyOrder<K> {
+    /// The keys in their old order.
+    pub old: Vec<K>,
+    /// The keys in their new order.
+    pub new: Vec<K>,
+}
+
+impl<K> Invertible for KeyOrder<K> {
+    fn invert(self) -> Self {
+        Self {
+            old: self.new,
+            new: self.old,
+        }
+    }
+}
+
+/// Records the order of the keys present on both sides, if configured to and it changed.
+fn key_order<'a, K>(
+    old: impl Iterator<Item = &'a K>,
+    new: impl Iterator<Item = &'a K>,
+    options: &DiffOptions,
+) -> Option<KeyOrder<K>>
+where
+    K: PartialEq + Clone + 'a,
+{
+    if !options.detect_reorder {
+        return None;
+    }
+    let old: Vec<K> = old.cloned().collect();
+    let new: Vec<K> = new.cloned().collect();
+    (old != new).then_some(KeyOrder { old, new })
+}
+
+/// Squashes the key orders of two consecutive diffs.
+fn compose_order<K>(first: Option<KeyOrder<K>>, then: Option<KeyOrder<K>>) -> Option<KeyOrder<K>> {
+    match (first, then) {
+        (Some(first), Some(then)) => Some(KeyOrder {
+            old: first.old,
+            new: then.new,
+        }),
+        (first, then) => first.or(then),
+    }
+}
+
+/// Rebases a key order onto the key order of a concurrent diff.
+fn rebase_order<K: PartialEq>(
+    order: Option<KeyOrder<K>>,
+    onto: &Option<KeyOrder<K>>,
+    rebaser: &mut Rebaser,
+) -> Option<KeyOrder<K>> {
+    match (order, onto) {
+        (Some(order), Some(onto)) => {
+            if order.new != onto.new {
+                rebaser.reject(RejectReason::Conflict);
+            }
+            None
+        }
+        (order, None) => order,
+        (None, Some(_)) => None,
+    }
+}
+
+/// Moves the `keys` present in `map` into their order in `keys`, within the slots they occupy.
+fn reorder<K, T, S>(map: &mut IndexMap<K, T, S>, keys: &[K])
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    let indices: Vec<usize> = keys.iter().filter_map(|k| map.get_index_of(k)).collect();
+    let mut slots = indices.clone();
+    slots.sort_unstable();
+
+    let mut targets: Vec<usize> = (0..map.len()).collect();
+    for (index, slot) in indices.into_iter().zip(slots) {
+        targets[index] = slot;
+    }
+
+    let mut entries: Vec<_> = (0..map.len()).map(|_| None).collect();
+    for (entry, target) in map.drain(..).zip(targets) {
+        entries[target] = Some(entry);
+    }
+    map.extend(entries.into_iter().flatten());
+}
+
+/// Represents the difference between two [`IndexMap`] collections.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IndexMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+{
+    /// Entry diffs, ordered like the keys of the old map followed by the added keys.
+    pub entries: IndexMap<K, CollectionDiffEntry<T>>,
+    /// New order of the keys present in both maps, if it changed and was detected.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub order: Option<KeyOrder<K>>,
+}
+
+impl<K, T> Default for IndexMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+{
+    fn default() -> Self {
+        Self {
+            entries: IndexMap::new(),
+            order: None,
+        }
+    }
+}
+
+impl<K, T> PartialEq for IndexMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable + PartialEq,
+    <T as Diffable>::Repr: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries && self.order == other.order
+    }
+}
+
+impl<K, T> Eq for IndexMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable + Eq,
+    <T as Diffable>::Repr: Eq,
+{
+}
+
+impl<K, T> Clone for IndexMapDiff<K, T>
+where
+    K: Hash + Eq + Clone,
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            order: self.order.clone(),
+        }
+    }
+}
+
+impl<K, T> Changeable for IndexMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+{
+    fn is_changed(&self) -> bool {
+        self.order.is_some() || self.entries.values().any(|v| v.is_changed())
+    }
+}
+
+impl<K, T, S> Diffable for IndexMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: Diffable + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    type Repr = IndexMapDiff<K, T>;
+
+    fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        let mut entries = IndexMap::new();
+
+        for (k, v) in self {
+            let other = b.get(k);
+            match other {
+                Some(other) => {
+                    let diff = v.diff_with(other, options);
+                    if diff.is_changed() {
+                        entries.insert(k.clone(), CollectionDiffEntry::Changed(diff))
+                    } else {
+                        entries.insert(k.clone(), CollectionDiffEntry::Unchanged)
+                    }
+                }
+                None => entries.insert(k.clone(), CollectionDiffEntry::Removed(v.clone())),
+            };
+        }
+
+        for (k, v) in b {
+            if entries.contains_key(k) {
+                continue;
+            }
+            entries.insert(k.clone(), CollectionDiffEntry::Added(v.clone()));
+        }
+
+        let order = key_order(
+            self.keys().filter(|k| b.contains_key(*k)),
+            b.keys().filter(|k| self.contains_key(*k)),
+            options,
+        );
+        IndexMapDiff { entries, order }
+    }
+}
+
+/// Key order isn't checked against the old order recorded in the diff.
+impl<K, T, S> Patchable for IndexMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: Patchable + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        for (k, entry) in &diff.entries {
+            match entry {
+                CollectionDiffEntry::Unchanged if !self.contains_key(k) => {
+                    return Err(PatchError::Missing)
+                }
+                CollectionDiffEntry::Unchanged => {}
+                CollectionDiffEntry::Changed(diff) => {
+                    self.get_mut(k).ok_or(PatchError::Missing)?.apply(diff)?
+                }
+                CollectionDiffEntry::Removed(old) => {
+                    let value = self.get(k).ok_or(PatchError::Missing)?;
+                    if value.diff(old).is_changed() {
+                        return Err(PatchError::Mismatch);
+                    }
+                    self.shift_remove(k);
+                }
+                CollectionDiffEntry::Added(_) if self.contains_key(k) => {
+                    return Err(PatchError::Exists)
+                }
+                CollectionDiffEntry::Added(new) => {
+                    self.insert(k.clone(), new.clone());
+                }
+            }
+        }
+
+        if let Some(order) = &diff.order {
+            reorder(self, &order.new);
+        }
+        Ok(())
+    }
+}
+
+impl<K, T> Invertible for IndexMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+    <T as Diffable>::Repr: Invertible,
+{
+    fn invert(self) -> Self {
+        Self {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|(k, v)| (k, v.invert()))
+                .collect(),
+            order: self.order.map(Invertible::invert),
+        }
+    }
+}
+
+impl<K: Hash + Eq, T: Diffable> MapEntries<K, T> for IndexMap<K, CollectionDiffEntry<T>> {
+    fn take(&mut self, k: &K) -> Option<CollectionDiffEntry<T>> {
+        self.shift_remove(k)
+    }
+
+    fn put(&mut self, k: K, entry: CollectionDiffEntry<T>) {
+        self.insert(k, entry);
+    }
+}
+
+impl<K, T> Composable for IndexMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Patchable,
+    <T as Diffable>::Repr: Composable + Invertible,
+{
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        Ok(Self {
+            entries: compose_map(self.entries, then.entries)?,
+            order: compose_order(self.order, then.order),
+        })
+    }
+}
+
+/// Reordering the keys differently than `onto` is rejected as a conflict.
+impl<K, T> Rebasable for IndexMapDiff<K, T>
+where
+    K: Hash + Eq + Debug,
+    T: Diffable,
+    <T as Diffable>::Repr: Rebasable,
+{
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        Self {
+            entries: rebase_map::<_, _, IndexMap<_, _>>(
+                self.entries,
+                |k| onto.entries.get(k),
+                rebaser,
+            ),
+            order: rebase_order(self.order, &onto.order, rebaser),
+        }
+    }
+}
+
+/// A changed key order is reported as a change of the whole map, before its entries.
+impl<K, T> Walkable for IndexMapDiff<K, T>
+where
+    K: Hash + Eq + Debug,
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        if let Some(KeyOrder { old, new }) = &self.order {
+            walker.report(Change::Changed { old, new });
+        }
+        walk_map(&self.entries, walker);
+    }
+}
+
+impl<K, T, S> Mergeable for IndexMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: Mergeable + Debug + Clone + 'static,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        let keys: IndexSet<_> = base.keys().chain(theirs.keys()).collect();
+
+        for k in keys {
+            let value = merger.scoped(PathSegment::key(k), |merger| {
+                merger.merge_present(self.get_mut(k), base.get(k), theirs.get(k))
+            });
+            match value {
+                Some(Some(value)) => {
+                    self.insert(k.clone(), value);
+                }
+                Some(None) => {
+                    self.shift_remove(k);
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+impl<K, T, S> Similar for IndexMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: Similar + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        let (pairs, total) = key_pairs(self.iter(), &|k| other.get(k), other.len());
+        similarity_of(&pairs, total)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        let (pairs, total) = key_pairs(self.iter(), &|k| other.get(k), other.len());
+        distance_of(&pairs, total - pairs.len())
+    }
+}
+
+/// Represents the difference between two [`IndexSet`] collections.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IndexSetDiff<T> {
+    /// Values only in the new set, in its order.
+    pub added: Vec<T>,
+    /// Values only in the old set, in its order.
+    pub removed: Vec<T>,
+    /// New order of the values present in both sets, if it changed and was detected.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub order: Option<KeyOrder<T>>,
+}
+
+impl<T> Default for IndexSetDiff<T> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            order: None,
+        }
+    }
+}
+
+impl<T> Changeable for IndexSetDiff<T> {
+    fn is_changed(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty() || self.order.is_some()
+    }
+}
+
+impl<T, S> Diffable for IndexSet<T, S>
+where
+    T: Hash + Eq + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    S: BuildHasher,
+{
+    type Repr = IndexSetDiff<T>;
+
+    fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
+        IndexSetDiff {
+            added: b.difference(self).cloned().collect(),
+            removed: self.difference(b).cloned().collect(),
+            order: key_order(self.intersection(b), b.intersection(self), options),
+        }
+    }
+}
+
+/// Value order isn't checked against the old order recorded in the diff.
+impl<T, S> Patchable for IndexSet<T, S>
+where
+    T: Hash + Eq + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        for value in &diff.removed {
+            if !self.shift_remove(value) {
+                return Err(PatchError::Missing);
+            }
+        }
+        for value in &diff.added {
+            if !self.insert(value.clone()) {
+                return Err(PatchError::Exists);
+            }
+        }
+
+        if let Some(order) = &diff.order {
+            let mut map: IndexMap<T, ()> = self.drain(..).map(|v| (v, ())).collect();
+            reorder(&mut map, &order.new);
+            self.extend(map.into_keys());
+        }
+        Ok(())
+    }
+}
+
+impl<T> Invertible for IndexSetDiff<T> {
+    fn invert(self) -> Self {
+        Self {
+            added: self.removed,
+            removed: self.added,
+            order: self.order.map(Invertible::invert),
+        }
+    }
+}
+
+/// Values removed and added back, or added and removed again, cancel out.
+impl<T: PartialEq> Composable for IndexSetDiff<T> {
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        let Self {
+            mut added,
+            mut removed,
+            order,
+        } = self;
+
+        for value in then.removed {
+            match added.iter().position(|v| *v == value) {
+                Some(i) => {
+                    added.remove(i);
+                }
+                None if removed.contains(&value) => return Err(PatchError::Missing),
+                None => removed.push(value),
+            }
+        }
+        for value in then.added {
+            match removed.iter().position(|v| *v == value) {
+                Some(i) => {
+                    removed.remove(i);
+                }
+                None if added.contains(&value) => return Err(PatchError::Exists),
+                None => added.push(value),
+            }
+        }
+
+        Ok(Self {
+            added,
+            removed,
+            order: compose_order(order, then.order),
+        })
+    }
+}
+
+/// Values added or removed by both diffs are dropped, reordering the values differently than
+/// `onto` is rejected as a conflict.
+impl<T: PartialEq> Rebasable for IndexSetDiff<T> {
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        Self {
+            added: (self.added.into_iter())
+                .filter(|v| !onto.added.contains(v))
+                .collect(),
+            removed: (self.removed.into_iter())
+                .filter(|v| !onto.removed.contains(v))
+                .collect(),
+            order: rebase_order(self.order, &onto.order, rebaser),
+        }
+    }
+}
+
+/// Added and removed values are reported under their own key.
+impl<T: Debug> Walkable for IndexSetDiff<T> {
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        if let Some(KeyOrder { old, new }) = &self.order {
+            walker.report(Change::Changed { old, new });
+        }
+        for value in &self.removed {
+            walker.scoped(PathSegment::key(value), |walker| {
+                walker.report(Change::Removed(value))
+            });
+        }
+        for value in &self.added {
+            walker.scoped(PathSegment::key(value), |walker| {
+                walker.report(Change::Added(value))
+            });
+        }
+    }
+}
+
+/// Our values stay in place, values only they added are appended.
+impl<T, S> Mergeable for IndexSet<T, S>
+where
+    T: Hash + Eq + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn merge(&mut self, base: &Self, theirs: &Self, _merger: &mut Merger) {
+        for value in base.difference(theirs) {
+            self.shift_remove(value);
+        }
+        for value in theirs.difference(base) {
+            self.insert(value.clone());
+        }
+    }
+}
+
+/// Sets are as similar as the share of their values they have in common.
+impl<T, S> Similar for IndexSet<T, S>
+where
+    T: Hash + Eq + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        let common = self.intersection(other).count();
+        let total = self.len() + other.len() - common;
+        if total == 0 {
+            return 1.0;
+        }
+        common as f64 / total as f64
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        let common = self.intersection(other).count();
+        (self.len() + other.len() - 2 * common) as f64
+    }
+}
@@ -0,0 +1,101 @@
+//! # Inverting diffs
+//!
+//! [`Invertible`] turns the diff from `a` to `b` into the diff from `b` back to `a`, without
+//! needing either value. Applying the inverted diff undoes the original one.
+//!
+//! ```rust
+//! use diffogus::diff::Diffable;
+//! use diffogus::invert::Invertible;
+//! use diffogus::patch::Patchable;
+//!
+//! let a = vec![1, 2, 3];
+//! let b = vec![1, 5];
+//! let diff = a.diff(&b);
+//!
+//! let mut value = a.clone();
+//! value.apply(&diff).unwrap();
+//! value.apply(&diff.invert()).unwrap();
+//! assert_eq!(a, value);
+//! ```
+
+use crate::diff::{
+    BTreeMapDiff, CollectionDiffEntry, Diffable, HashMapDiff, OptionDiff, PrimitiveDiff, VecDiff,
+};
+use std::hash::Hash;
+
+/// Trait for diff representations that can be reversed.
+pub trait Invertible {
+    /// Returns the reverse change, swapping `old` with `new` and `Added` with `Removed`.
+    fn invert(self) -> Self;
+}
+
+impl<T: Diffable> Invertible for PrimitiveDiff<T> {
+    fn invert(self) -> Self {
+        match self {
+            Self::Changed { old, new } => Self::Changed { old: new, new: old },
+            Self::Unchanged => Self::Unchanged,
+        }
+    }
+}
+
+impl<T> Invertible for CollectionDiffEntry<T>
+where
+    T: Diffable,
+    <T as Diffable>::Repr: Invertible,
+{
+    fn invert(self) -> Self {
+        match self {
+            Self::Removed(v) => Self::Added(v),
+            Self::Added(v) => Self::Removed(v),
+            Self::Changed(diff) => Self::Changed(diff.invert()),
+            Self::Unchanged => Self::Unchanged,
+        }
+    }
+}
+
+impl<T> Invertible for OptionDiff<T>
+where
+    T: Diffable,
+    <T as Diffable>::Repr: Invertible,
+{
+    fn invert(self) -> Self {
+        match self {
+            Self::Removed(v) => Self::Added(v),
+            Self::Added(v) => Self::Removed(v),
+            Self::Changed(diff) => Self::Changed(diff.invert()),
+            Self::Unchanged => Self::Unchanged,
+        }
+    }
+}
+
+impl<T> Invertible for VecDiff<T>
+where
+    T: Diffable,
+    <T as Diffable>::Repr: Invertible,
+{
+    fn invert(self) -> Self {
+        Self(self.0.into_iter().map(Invertible::invert).collect())
+    }
+}
+
+impl<K, T> Invertible for HashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+    <T as Diffable>::Repr: Invertible,
+{
+    fn invert(self) -> Self {
+        Self(self.0.into_iter().map(|(k, v)| (k, v.invert())).collect())
+    }
+}
+
+impl<K, T> Invertible for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable,
+    <T as Diffable>::Repr: Invertible,
+{
+    fn invert(self) -> Self {
+        Self(self.0.into_iter().map(|(k, v)| (k, v.invert())).collect())
+    }
+}
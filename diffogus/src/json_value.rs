@@ -11,13 +11,24 @@
 //!```
 //!
 
-use crate::diff::{Changeable, CollectionDiffEntry, Diffable, PrimitiveDiff, VecDiff};
+use crate::compose::{compose_map, Composable};
+use crate::diff::{Changeable, CollectionDiffEntry, DiffOptions, Diffable, PrimitiveDiff, VecDiff};
+use crate::diff_ref::{CollectionDiffEntryRef, DiffableRef, ToOwnedDiff, VecDiffRef};
+use crate::invert::Invertible;
+use crate::merge::{Mergeable, Merger};
+use crate::patch::{PatchError, Patchable};
+use crate::path::PathSegment;
+use crate::rebase::{rebase_map, Rebasable, Rebaser, RejectReason};
+use crate::similarity::{
+    distance_of, key_pairs, similarity_of, str_distance, str_similarity, Similar,
+};
+use crate::visit::{walk_map, Change, Walkable, Walker};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Number, Value};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Represents the difference between two [`Map`] collections.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValueMapDiff(pub BTreeMap<String, CollectionDiffEntry<Value>>);
 
 impl PartialEq for ValueMapDiff {
@@ -26,23 +37,33 @@ impl PartialEq for ValueMapDiff {
     }
 }
 
+impl Eq for ValueMapDiff {}
+
 impl Changeable for ValueMapDiff {
     fn is_changed(&self) -> bool {
         self.0.values().any(|v| v.is_changed())
     }
 }
 
+/// Keys ignored by the [`DiffOptions`] are left out of the diff.
 impl Diffable for Map<String, Value> {
     type Repr = ValueMapDiff;
 
     fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
         let mut out = BTreeMap::new();
 
         for (k, v) in self {
+            if options.is_ignored(k) {
+                continue;
+            }
             let other = b.get(k);
             match other {
                 Some(other) => {
-                    let diff = v.diff(other);
+                    let diff = v.diff_with(other, options);
                     if diff.is_changed() {
                         out.insert(k.clone(), CollectionDiffEntry::Changed(diff))
                     } else {
@@ -54,7 +75,7 @@ impl Diffable for Map<String, Value> {
         }
 
         for (k, v) in b {
-            if out.contains_key(k) {
+            if out.contains_key(k) || options.is_ignored(k) {
                 continue;
             }
             out.insert(k.clone(), CollectionDiffEntry::Added(v.clone()));
@@ -65,7 +86,7 @@ impl Diffable for Map<String, Value> {
 }
 
 /// Enum representing a difference between two [`Value`]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "value")]
 pub enum ValueDiff {
     /// Indicates that the value has not changed.
@@ -107,6 +128,7 @@ pub enum ValueDiff {
 impl PartialEq for ValueDiff {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Self::Unchanged, Self::Unchanged) => true,
             (
                 Self::VariantChanged {
                     old: a_old,
@@ -154,42 +176,49 @@ impl PartialEq for ValueDiff {
     }
 }
 
+impl Eq for ValueDiff {}
+
 impl Changeable for ValueDiff {
     fn is_changed(&self) -> bool {
         !matches!(self, Self::Unchanged)
     }
 }
 
+/// Numbers are compared with the tolerance of the [`DiffOptions`] when one is set.
 impl Diffable for Value {
     type Repr = ValueDiff;
 
     fn diff(&self, b: &Self) -> Self::Repr {
+        self.diff_with(b, &DiffOptions::default())
+    }
+
+    fn diff_with(&self, b: &Self, options: &DiffOptions) -> Self::Repr {
         match (self, b) {
             (Self::Null, Self::Null) => ValueDiff::Unchanged,
             (Self::Bool(a), Self::Bool(b)) => match a.diff(b) {
                 PrimitiveDiff::Changed { old, new } => ValueDiff::BoolChanged { old, new },
                 PrimitiveDiff::Unchanged => ValueDiff::Unchanged,
             },
-            (Self::Number(na), Self::Number(nb)) => match na == nb {
+            (Self::Number(na), Self::Number(nb)) => match number_eq(na, nb, options) {
                 true => ValueDiff::Unchanged,
                 false => ValueDiff::NumberChanged {
                     old: na.clone(),
                     new: nb.clone(),
                 },
             },
-            (Self::String(a), Self::String(b)) => match a.diff(b) {
+            (Self::String(a), Self::String(b)) => match a.diff_with(b, options) {
                 PrimitiveDiff::Changed { old, new } => ValueDiff::StringChanged { old, new },
                 PrimitiveDiff::Unchanged => ValueDiff::Unchanged,
             },
             (Self::Array(a), Self::Array(b)) => {
-                let diff = a.diff(b);
+                let diff = a.diff_with(b, options);
                 match diff.is_changed() {
                     true => ValueDiff::ArrayChanged(diff),
                     false => ValueDiff::Unchanged,
                 }
             }
             (Self::Object(a), Self::Object(b)) => {
-                let diff = a.diff(b);
+                let diff = a.diff_with(b, options);
                 match diff.is_changed() {
                     true => ValueDiff::ObjectChanged(diff),
                     false => ValueDiff::Unchanged,
@@ -202,3 +231,447 @@ impl Diffable for Value {
         }
     }
 }
+
+fn number_eq(a: &Number, b: &Number, options: &DiffOptions) -> bool {
+    match (&options.tolerance, a.as_f64(), b.as_f64()) {
+        (Some(tolerance), Some(a), Some(b)) => tolerance.approx_eq(a, b),
+        _ => a == b,
+    }
+}
+
+/// Borrowed counterpart of [`ValueMapDiff`].
+#[derive(Debug, Serialize)]
+pub struct ValueMapDiffRef<'a>(pub BTreeMap<&'a str, CollectionDiffEntryRef<'a, Value>>);
+
+impl Changeable for ValueMapDiffRef<'_> {
+    fn is_changed(&self) -> bool {
+        self.0.values().any(|v| v.is_changed())
+    }
+}
+
+impl ToOwnedDiff for ValueMapDiffRef<'_> {
+    type Owned = ValueMapDiff;
+
+    fn to_owned(&self) -> Self::Owned {
+        ValueMapDiff(
+            self.0
+                .iter()
+                .map(|(k, entry)| (k.to_string(), entry.to_owned()))
+                .collect(),
+        )
+    }
+}
+
+impl DiffableRef for Map<String, Value> {
+    type ReprRef<'a> = ValueMapDiffRef<'a>;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        let mut out = BTreeMap::new();
+
+        for (k, v) in self {
+            if options.is_ignored(k) {
+                continue;
+            }
+            let other = b.get(k);
+            match other {
+                Some(other) => {
+                    let diff = v.diff_ref_with(other, options);
+                    if diff.is_changed() {
+                        out.insert(k.as_str(), CollectionDiffEntryRef::Changed(diff))
+                    } else {
+                        out.insert(k.as_str(), CollectionDiffEntryRef::Unchanged)
+                    }
+                }
+                None => out.insert(k.as_str(), CollectionDiffEntryRef::Removed(v)),
+            };
+        }
+
+        for (k, v) in b {
+            if out.contains_key(k.as_str()) || options.is_ignored(k) {
+                continue;
+            }
+            out.insert(k.as_str(), CollectionDiffEntryRef::Added(v));
+        }
+
+        ValueMapDiffRef(out)
+    }
+}
+
+/// Borrowed counterpart of [`ValueDiff`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
+pub enum ValueDiffRef<'a> {
+    /// Indicates that the value has not changed.
+    Unchanged,
+    /// Indicated that the enum variant has changed.
+    VariantChanged {
+        /// Field holding the old value
+        old: &'a Value,
+        /// Field holding the new value
+        new: &'a Value,
+    },
+    /// Indicated that [`Value::Bool`] value has changed.
+    BoolChanged {
+        /// Field holding the old value
+        old: &'a bool,
+        /// Field holding the new value
+        new: &'a bool,
+    },
+    /// Indicated that [`Value::String`] value has changed.
+    StringChanged {
+        /// Field holding the old value
+        old: &'a str,
+        /// Field holding the new value
+        new: &'a str,
+    },
+    /// Indicated that [`Value::Number`] value has changed.
+    NumberChanged {
+        /// Field holding the old value
+        old: &'a Number,
+        /// Field holding the new value
+        new: &'a Number,
+    },
+    /// Indicates that [`Value::Array`] values have changed.
+    ArrayChanged(VecDiffRef<'a, Value>),
+    /// Indicates that [`Value::Object`] values have changed.
+    ObjectChanged(ValueMapDiffRef<'a>),
+}
+
+impl Changeable for ValueDiffRef<'_> {
+    fn is_changed(&self) -> bool {
+        !matches!(self, Self::Unchanged)
+    }
+}
+
+impl ToOwnedDiff for ValueDiffRef<'_> {
+    type Owned = ValueDiff;
+
+    fn to_owned(&self) -> Self::Owned {
+        match self {
+            Self::Unchanged => ValueDiff::Unchanged,
+            Self::VariantChanged { old, new } => ValueDiff::VariantChanged {
+                old: (*old).clone(),
+                new: (*new).clone(),
+            },
+            Self::BoolChanged { old, new } => ValueDiff::BoolChanged {
+                old: **old,
+                new: **new,
+            },
+            Self::StringChanged { old, new } => ValueDiff::StringChanged {
+                old: old.to_string(),
+                new: new.to_string(),
+            },
+            Self::NumberChanged { old, new } => ValueDiff::NumberChanged {
+                old: (*old).clone(),
+                new: (*new).clone(),
+            },
+            Self::ArrayChanged(diff) => ValueDiff::ArrayChanged(diff.to_owned()),
+            Self::ObjectChanged(diff) => ValueDiff::ObjectChanged(diff.to_owned()),
+        }
+    }
+}
+
+impl DiffableRef for Value {
+    type ReprRef<'a> = ValueDiffRef<'a>;
+
+    fn diff_ref_with<'a>(&'a self, b: &'a Self, options: &DiffOptions) -> Self::ReprRef<'a> {
+        match (self, b) {
+            (Self::Null, Self::Null) => ValueDiffRef::Unchanged,
+            (Self::Bool(a), Self::Bool(b)) => match a == b {
+                true => ValueDiffRef::Unchanged,
+                false => ValueDiffRef::BoolChanged { old: a, new: b },
+            },
+            (Self::Number(na), Self::Number(nb)) => match number_eq(na, nb, options) {
+                true => ValueDiffRef::Unchanged,
+                false => ValueDiffRef::NumberChanged { old: na, new: nb },
+            },
+            (Self::String(a), Self::String(b)) => match options.str_eq(a, b) {
+                true => ValueDiffRef::Unchanged,
+                false => ValueDiffRef::StringChanged { old: a, new: b },
+            },
+            (Self::Array(a), Self::Array(b)) => {
+                let diff = a.diff_ref_with(b, options);
+                match diff.is_changed() {
+                    true => ValueDiffRef::ArrayChanged(diff),
+                    false => ValueDiffRef::Unchanged,
+                }
+            }
+            (Self::Object(a), Self::Object(b)) => {
+                let diff = a.diff_ref_with(b, options);
+                match diff.is_changed() {
+                    true => ValueDiffRef::ObjectChanged(diff),
+                    false => ValueDiffRef::Unchanged,
+                }
+            }
+            _ => ValueDiffRef::VariantChanged { old: self, new: b },
+        }
+    }
+}
+
+impl Patchable for Map<String, Value> {
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        let ValueMapDiff(entries) = diff;
+
+        for (k, entry) in entries {
+            match entry {
+                CollectionDiffEntry::Unchanged if !self.contains_key(k) => {
+                    return Err(PatchError::Missing)
+                }
+                CollectionDiffEntry::Unchanged => {}
+                CollectionDiffEntry::Changed(diff) => {
+                    self.get_mut(k).ok_or(PatchError::Missing)?.apply(diff)?
+                }
+                CollectionDiffEntry::Removed(old) => {
+                    if self.get(k).ok_or(PatchError::Missing)? != old {
+                        return Err(PatchError::Mismatch);
+                    }
+                    self.remove(k);
+                }
+                CollectionDiffEntry::Added(_) if self.contains_key(k) => {
+                    return Err(PatchError::Exists)
+                }
+                CollectionDiffEntry::Added(new) => {
+                    self.insert(k.clone(), new.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Patchable for Value {
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        match (diff, self) {
+            (ValueDiff::Unchanged, _) => {}
+            (ValueDiff::VariantChanged { old, new }, value) => {
+                if value != old {
+                    return Err(PatchError::Mismatch);
+                }
+                *value = new.clone();
+            }
+            (ValueDiff::BoolChanged { old, new }, Self::Bool(value)) if value == old => {
+                *value = *new
+            }
+            (ValueDiff::StringChanged { old, new }, Self::String(value)) if value == old => {
+                *value = new.clone()
+            }
+            (ValueDiff::NumberChanged { old, new }, Self::Number(value)) if value == old => {
+                *value = new.clone()
+            }
+            (ValueDiff::ArrayChanged(diff), Self::Array(value)) => value.apply(diff)?,
+            (ValueDiff::ObjectChanged(diff), Self::Object(value)) => value.apply(diff)?,
+            _ => return Err(PatchError::Mismatch),
+        }
+        Ok(())
+    }
+}
+
+impl Invertible for ValueMapDiff {
+    fn invert(self) -> Self {
+        Self(self.0.into_iter().map(|(k, v)| (k, v.invert())).collect())
+    }
+}
+
+impl Invertible for ValueDiff {
+    fn invert(self) -> Self {
+        match self {
+            Self::Unchanged => Self::Unchanged,
+            Self::VariantChanged { old, new } => Self::VariantChanged { old: new, new: old },
+            Self::BoolChanged { old, new } => Self::BoolChanged { old: new, new: old },
+            Self::StringChanged { old, new } => Self::StringChanged { old: new, new: old },
+            Self::NumberChanged { old, new } => Self::NumberChanged { old: new, new: old },
+            Self::ArrayChanged(diff) => Self::ArrayChanged(diff.invert()),
+            Self::ObjectChanged(diff) => Self::ObjectChanged(diff.invert()),
+        }
+    }
+}
+
+impl Composable for ValueMapDiff {
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        compose_map(self.0, then.0).map(Self)
+    }
+}
+
+impl ValueDiff {
+    /// Splits a change that records both values in full into `(old, new)`.
+    fn into_values(self) -> Result<(Value, Value), Self> {
+        match self {
+            Self::VariantChanged { old, new } => Ok((old, new)),
+            Self::BoolChanged { old, new } => Ok((Value::Bool(old), Value::Bool(new))),
+            Self::StringChanged { old, new } => Ok((Value::String(old), Value::String(new))),
+            Self::NumberChanged { old, new } => Ok((Value::Number(old), Value::Number(new))),
+            diff => Err(diff),
+        }
+    }
+
+    /// New value of a change that records it in full.
+    fn new_value(&self) -> Option<Value> {
+        match self {
+            Self::VariantChanged { new, .. } => Some(new.clone()),
+            Self::BoolChanged { new, .. } => Some(Value::Bool(*new)),
+            Self::StringChanged { new, .. } => Some(Value::String(new.clone())),
+            Self::NumberChanged { new, .. } => Some(Value::Number(new.clone())),
+            _ => None,
+        }
+    }
+}
+
+impl Composable for ValueDiff {
+    fn compose(self, then: Self) -> Result<Self, PatchError> {
+        let diff = match (self, then) {
+            (Self::Unchanged, diff) | (diff, Self::Unchanged) => diff,
+            (Self::ArrayChanged(first), Self::ArrayChanged(then)) => {
+                Self::ArrayChanged(first.compose(then)?)
+            }
+            (Self::ObjectChanged(first), Self::ObjectChanged(then)) => {
+                Self::ObjectChanged(first.compose(then)?)
+            }
+            // When one side records full values, the other one can be applied to them.
+            (first, then) => match first.into_values() {
+                Ok((old, mut value)) => {
+                    value.apply(&then)?;
+                    old.diff(&value)
+                }
+                Err(first) => {
+                    let (mut value, new) = then.into_values().map_err(|_| PatchError::Mismatch)?;
+                    value.apply(&first.invert())?;
+                    value.diff(&new)
+                }
+            },
+        };
+
+        Ok(if diff.is_changed() {
+            diff
+        } else {
+            Self::Unchanged
+        })
+    }
+}
+
+impl Mergeable for Map<String, Value> {
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        let keys: BTreeSet<_> = base.keys().chain(theirs.keys()).collect();
+
+        for k in keys {
+            let value = merger.scoped(PathSegment::key(k), |merger| {
+                merger.merge_present(self.get_mut(k), base.get(k), theirs.get(k))
+            });
+            match value {
+                Some(Some(value)) => {
+                    self.insert(k.clone(), value);
+                }
+                Some(None) => {
+                    self.remove(k);
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+impl Mergeable for Value {
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        match (self, base, theirs) {
+            (Value::Object(ours), Value::Object(base), Value::Object(theirs)) => {
+                ours.merge(base, theirs, merger)
+            }
+            (Value::Array(ours), Value::Array(base), Value::Array(theirs)) => {
+                ours.merge(base, theirs, merger)
+            }
+            (ours, base, theirs) => merger.merge_leaf(ours, base, theirs),
+        }
+    }
+}
+
+impl Rebasable for ValueMapDiff {
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        Self(rebase_map::<_, _, BTreeMap<_, _>>(
+            self.0,
+            |k| onto.0.get(k),
+            rebaser,
+        ))
+    }
+}
+
+impl Rebasable for ValueDiff {
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        let diff = match (self, onto) {
+            (diff, Self::Unchanged) => diff,
+            (Self::Unchanged, _) => Self::Unchanged,
+            (Self::ArrayChanged(diff), Self::ArrayChanged(onto)) => {
+                Self::ArrayChanged(diff.rebase(onto, rebaser))
+            }
+            (Self::ObjectChanged(diff), Self::ObjectChanged(onto)) => {
+                Self::ObjectChanged(diff.rebase(onto, rebaser))
+            }
+            // Replacing the value only agrees with the other diff if both end up the same.
+            (diff, onto) => {
+                let new = diff.new_value();
+                if new.is_none() || new != onto.new_value() {
+                    rebaser.reject(RejectReason::Conflict);
+                }
+                Self::Unchanged
+            }
+        };
+
+        if diff.is_changed() {
+            diff
+        } else {
+            Self::Unchanged
+        }
+    }
+}
+
+impl Walkable for ValueMapDiff {
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        walk_map(&self.0, walker);
+    }
+}
+
+impl Walkable for ValueDiff {
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        match self {
+            Self::Unchanged => walker.report_unchanged(),
+            Self::VariantChanged { old, new } => walker.report(Change::Changed { old, new }),
+            Self::BoolChanged { old, new } => walker.report(Change::Changed { old, new }),
+            Self::StringChanged { old, new } => walker.report(Change::Changed { old, new }),
+            Self::NumberChanged { old, new } => walker.report(Change::Changed { old, new }),
+            Self::ArrayChanged(diff) => diff.walk_with(walker),
+            Self::ObjectChanged(diff) => diff.walk_with(walker),
+        }
+    }
+}
+
+impl Similar for Map<String, Value> {
+    fn similarity(&self, other: &Self) -> f64 {
+        let (pairs, total) = key_pairs(self.iter(), &|k| other.get(k), other.len());
+        similarity_of(&pairs, total)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        let (pairs, total) = key_pairs(self.iter(), &|k| other.get(k), other.len());
+        distance_of(&pairs, total - pairs.len())
+    }
+}
+
+impl Similar for Value {
+    fn similarity(&self, other: &Self) -> f64 {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => str_similarity(a, b),
+            (Self::Array(a), Self::Array(b)) => a.similarity(b),
+            (Self::Object(a), Self::Object(b)) => a.similarity(b),
+            (a, b) if a == b => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => str_distance(a, b),
+            (Self::Array(a), Self::Array(b)) => a.distance(b),
+            (Self::Object(a), Self::Object(b)) => a.distance(b),
+            (a, b) if a == b => 0.0,
+            _ => 1.0,
+        }
+    }
+}
@@ -20,6 +20,19 @@
 //!
 //! - [`diff::Changeable`] - A trait for types that can report whether they have changed.
 //! - [`diff::Diffable`] - A trait for types that can compute a difference with another instance of the same type.
+//! - [`diff::ApproxDiffable`] - A trait for types containing floats that can be diffed with a [`diff::Tolerance`].
+//! - [`diff_ref::DiffableRef`] - A trait for types that can compute a diff borrowing from both instances instead of cloning.
+//! - [`sparse::SparseDiffable`] - A trait for collections that can compute a diff storing only the changed entries.
+//! - [`patch::Patchable`] - A trait for types that can apply a diff to turn into the other instance.
+//! - [`invert::Invertible`] - A trait for diffs that can be reversed to undo a change.
+//! - [`compose::Composable`] - A trait for diffs that can be squashed with the diff that follows them.
+//! - [`merge::Mergeable`] - A trait for types whose concurrent changes can be merged with [`merge::merge3`].
+//! - [`rebase::Rebasable`] - A trait for diffs that can be rebased onto a concurrent diff with [`rebase::rebase`].
+//! - [`visit::Walkable`] - A trait for diffs whose leaf changes can be reported to a [`visit::DiffVisitor`] with their path.
+//! - [`similarity::Similar`] - A trait for types whose values can be scored by how close they are to each other.
+//!
+//! [`stats::DiffStats`] summarizes any walkable diff as counts of added, removed, changed and
+//! unchanged values.
 //!
 //! ## Supported Types
 //!
@@ -27,14 +40,20 @@
 //!
 //! Full list of types:
 //!
-//! - Primitive types: `u8`, `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, `i64`, `f32`, `f64`, `bool`, and `String`.
-//! - Collections: `HashMap<K, V>`, `Vec<T>`.
+//! - Primitive types: all integers including `usize`, `isize` and the `NonZero` ones, `f32`,
+//!   `f64`, `bool`, `char` and `()`.
+//! - Strings: `String`, `str`, `Box<str>`, `Rc<str>`, `Arc<str>` and `Cow<'_, str>`, all diffed
+//!   into a `String` diff.
+//! - Collections: `HashMap<K, V, S>` with any hasher, `BTreeMap<K, V>`, `Vec<T>`, `[T]`.
+//! - References: `&T` diffs like `T`, so `&str` and `&[T]` produce owned diffs.
 //! - Containers: `Option<T>`.
+//! - Wrappers: [`diff::Atomic<T>`] for any `T: PartialEq + Clone`, `Wrapping<T>` diffs like `T`.
 //!
 //! ## Features
 //!
 //! - **`serde`**: Enables support for serializing diff results using `serde`.
 //! - **`derive`**: Enables support for [`Diff`] derive macro.
+//! - **`indexmap`**: Enables diffing of `IndexMap` and `IndexSet`, optionally detecting reordered keys.
 //!
 //! ## Usage
 //!
@@ -88,6 +107,21 @@
 //! }
 //! ```
 //!
+//! ### Diff options
+//!
+//! [`diff::Diffable::diff_with`] takes [`diff::DiffOptions`] to compare floats with a tolerance,
+//! compare strings ignoring case, line up `Vec` items along their longest common subsequence
+//! or ignore fields by name. [`diff::Diffable::diff`] uses the defaults. Hand-written impls only
+//! have to provide `diff`; unless they override `diff_with` too, they ignore the options.
+//!
+//! ```rust
+//! use diffogus::diff::{Changeable, DiffOptions, Diffable, VecAlgorithm};
+//!
+//! let options = DiffOptions::new().vec_algorithm(VecAlgorithm::Lcs);
+//! let diff = vec![1, 2, 3].diff_with(&vec![0, 1, 2, 3], &options);
+//! assert_eq!(1, diff.0.iter().filter(|entry| entry.is_changed()).count());
+//! ```
+//!
 //! ### Serde Integration
 //!
 //! If you want to serialize the diff result (e.g., to JSON), enable the `serde` feature:
@@ -100,6 +134,7 @@
 //! Example usage:
 //!
 //! ```no_run
+//! # #[cfg(feature = "serde")] {
 //! use diffogus::diff::{Diffable, HashMapDiff};
 //! use serde_json;
 //! use std::collections::HashMap;
@@ -114,6 +149,7 @@
 //! let serialized = serde_json::to_string(&diff).unwrap();
 //!
 //! println!("Serialized diff: {}", serialized);
+//! # }
 //! ```
 //!
 //! ### Derive macro
@@ -128,6 +164,7 @@
 //! Example usage:
 //!
 //! ```no_run
+//! # #[cfg(feature = "derive")] {
 //! use diffogus::diff::{Diffable, PrimitiveDiff};
 //! use diffogus_derive::Diff;
 //!
@@ -142,8 +179,111 @@
 //!
 //! let diff = a.diff(&b);
 //! // Now do whatever you want with this diff.
+//! # }
+//! ```
+//!
+//! Fields marked with `#[diff(skip)]` are left out of the generated diff entirely, so their
+//! types don't have to implement [`diff::Diffable`].
+//!
+//! A field can also be diffed by a custom module with `#[diff(with = "path::to::module")]`. The
+//! module has to provide a `Repr` type, used in place of `<T as Diffable>::Repr`, and a
+//! `fn diff(a: &T, b: &T) -> Repr` function. Types opting into [`patch::Patchable`] also need a
+//! `fn apply(value: &mut T, diff: &Repr) -> Result<(), PatchError>` function in the module. This
+//! is handy for foreign types or for values that need bespoke comparison, e.g. a `Vec` that is
+//! really a set. `Repr` has to meet the same requirements as [`diff::Diffable::Repr`] and
+//! implement `Default`, plus [`invert::Invertible`], [`compose::Composable`],
+//! [`rebase::Rebasable`] and [`visit::Walkable`] for the opt-in impls below; all diff types in
+//! this crate do.
+//!
+//! Derived impls forward [`diff::DiffOptions`] to their fields and report fields named in
+//! [`diff::DiffOptions::ignore`] as unchanged. With the `serde` feature fields are named as in
+//! the serialized diff, see the renames below. Fields using `with`, `atomic` or a tolerance
+//! attribute are compared the same way regardless of the options.
+//!
+//! Types without a meaningful structural diff can be compared as a whole through `PartialEq`
+//! with `#[diff(atomic)]`, either on a single field or on the whole type. Atomic fields are
+//! wrapped in [`diff::Atomic`], so their types only need `PartialEq + Clone + Debug`. Atomic
+//! types are their own diff, so of the opt-in impls below they only take `patch`, `merge` and
+//! `similarity`.
+//!
+//! Float fields, including floats inside `Option` and `Vec`, can be compared with a tolerance
+//! through `#[diff(tolerance = 1e-3)]` (absolute) and `#[diff(relative_tolerance = 0.005)]`
+//! (relative to the larger magnitude). Both can be combined; see [`diff::Tolerance`].
+//!
+//! With the `serde` feature, `#[serde(rename_all = "...")]` on the type and
+//! `#[serde(rename = "...")]` on its fields are forwarded to the generated diff type, so diff keys
+//! match the serialized model. For enums, variant `rename_all` and `rename_all_fields` apply to
+//! the fields of the `<Variant>Changed` variants. `#[diff(rename_all = "...")]` and
+//! `#[diff(rename = "...")]` override the forwarded names for the diff only. Paths reported by
+//! derived impls name fields the same way.
+//!
+//! A named field holding another derived struct can be marked with `#[diff(flatten)]`, so the
+//! fields of its diff are serialized inline in the outer diff, mirroring `#[serde(flatten)]` on
+//! the model. Unchanged nested fields are left out just like the outer ones, and paths into
+//! them skip the flattened field.
+//!
+//! The generated type is called `<Type>DIff` by default; pick another name with
+//! `#[diff(name = "UserChanges")]`. Extra derives and attributes can be added to it with
+//! `#[diff(derive(Clone, PartialEq, Eq, Hash))]` and `#[diff(attr(non_exhaustive))]`. All diff
+//! types in this crate implement `Clone`, `PartialEq` and `Eq` (and `Hash` where their contents
+//! allow), so those derives work whenever the field types support them.
+//!
+//! With `#[diff(patch)]`, derived types implement [`patch::Patchable`] as well, so a diff can be
+//! applied back onto the original value. Skipped fields are left as they are. The impl is opt-in
+//! because every field type has to implement [`patch::Patchable`] too, which types with a
+//! hand-written `Diffable` impl or borrowed fields may not. With `#[diff(invert)]` the generated
+//! diff type implements [`invert::Invertible`], so recorded changes can be undone, and with
+//! `#[diff(compose)]` [`compose::Composable`], so they can be squashed. Composing enum diffs
+//! applies and inverts them, so enums need `patch` and `invert` for `compose`.
+//!
+//! With `#[diff(merge)]` derived types implement [`merge::Mergeable`], merging struct fields and
+//! the fields of matching enum variants one by one. Fields using `with`, `atomic` or a tolerance
+//! are merged as a whole, so their types need `Clone + Debug`. With `#[diff(rebase)]` the
+//! generated diff type implements [`rebase::Rebasable`], rebasing the diffs of struct fields and
+//! of matching enum variants the same way.
+//!
+//! With `#[diff(walk)]` the generated diff type implements [`visit::Walkable`], reporting changed
+//! fields under their names and changed enum variants as a whole.
+//!
+//! With `#[diff(similarity)]` derived types implement [`similarity::Similar`], averaging the
+//! similarities of their fields, or of the fields of matching enum variants. Fields count
+//! equally unless weighted with `#[diff(weight = 2.0)]`, which is only accepted together with
+//! `similarity` and can't be negative. Fields using `with`, `atomic` or a tolerance are either
+//! equal or completely different; all other field types have to implement
+//! [`similarity::Similar`]. Atomic types with the flag are either equal or completely different
+//! as a whole.
+//!
+//! Tuple structs and unit structs are supported too; the diff of a newtype such as
+//! `struct UserId(u64)` serializes exactly like the diff of the wrapped value.
+//!
+//! Generic types get `Diffable` bounds on their type parameters and on every field type that
+//! uses them. If those inferred bounds don't fit, replace them with `#[diff(bound = "...")]`:
+//!
+//! ```no_run
+//! # #[cfg(feature = "derive")] {
+//! use diffogus::diff::{Diffable, PrimitiveDiff};
+//! use diffogus::Diff;
+//!
+//! #[derive(Debug, Diff)]
+//! #[diff(bound = "T: Copy + Diffable<Repr = PrimitiveDiff<T>> + for<'a> diffogus::MySerialize<'a>")]
+//! struct Pair<T> {
+//!     left: T,
+//!     right: T,
+//! }
+//! # }
 //! ```
 //!
+//! Types with lifetime parameters, such as `struct Row<'a> { name: &'a str, tags: &'a [String] }`,
+//! are supported too. Borrowed fields diff like the value they point to, and when every field
+//! diff is owned the generated type drops the lifetimes, so the diff can outlive the inputs.
+//! Enums can't have lifetime parameters, as the diff of a changed variant holds the old and new
+//! value.
+//!
+//! Enums are supported as well. The generated diff reports `VariantChanged { old, new }` when the
+//! variant differs and a `<Variant>Changed` with nested field diffs when it stays the same, so
+//! the enum has to implement `Clone`. A variant named `Variant` can't have fields, as its
+//! `VariantChanged` would clash with the variant reporting a changed variant.
+//!
 
 #![cfg_attr(docsrs, feature(doc_cfg, rustdoc_internals))]
 
@@ -161,14 +301,57 @@ impl<'de, T> MySerialize<'de> for T where T: Serialize + Deserialize<'de> {}
 #[cfg(not(feature = "serde"))]
 impl<T> MySerialize<'_> for T {}
 
+/// Custom trait to allow for conditional serde support of borrowed diffs, which can only be
+/// serialized
+#[cfg(feature = "serde")]
+pub trait MySerializeRef: Serialize {}
+
+/// Custom trait to allow for conditional serde support of borrowed diffs, which can only be
+/// serialized
+#[cfg(not(feature = "serde"))]
+pub trait MySerializeRef {}
+
+#[cfg(feature = "serde")]
+impl<T> MySerializeRef for T where T: Serialize + ?Sized {}
+
+#[cfg(not(feature = "serde"))]
+impl<T: ?Sized> MySerializeRef for T {}
+
 /// Core diffing implementation
 pub mod diff;
 
+pub mod diff_ref;
+
+pub mod sparse;
+
+pub mod patch;
+
+pub mod invert;
+
+pub mod compose;
+
+pub mod path;
+
+pub mod merge;
+
+pub mod rebase;
+
+pub mod visit;
+
+pub mod stats;
+
+pub mod similarity;
+
 /// Diffing implementation for `serde_json::Value`
 #[cfg(feature = "json_value")]
 #[cfg_attr(docsrs, doc(cfg(feature = "json_value")))]
 pub mod json_value;
 
+/// Diffing implementation for `indexmap::IndexMap` and `indexmap::IndexSet`
+#[cfg(feature = "indexmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "indexmap")))]
+pub mod indexmap;
+
 #[cfg(feature = "diffogus_derive")]
 extern crate diffogus_derive;
 
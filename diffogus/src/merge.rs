@@ -0,0 +1,357 @@
+//! # Three-way merging
+//!
+//! [`merge3`] combines two sets of changes made to the same base value. Changes to different
+//! parts of the value, such as different struct fields, map keys or JSON object keys, are merged
+//! automatically. When both sides change the same part in different ways, the merged value keeps
+//! our side and a [`Conflict`] is reported with its [`Path`] and both values.
+//!
+//! ```rust
+//! use diffogus::merge::merge3;
+//! use std::collections::HashMap;
+//!
+//! let base = HashMap::from([("theme".to_string(), 1), ("size".to_string(), 10)]);
+//! let ours = HashMap::from([("theme".to_string(), 2), ("size".to_string(), 10)]);
+//! let theirs = HashMap::from([("theme".to_string(), 3), ("size".to_string(), 12)]);
+//!
+//! let merge = merge3(&base, &ours, &theirs);
+//! assert_eq!(Some(&12), merge.value.get("size"));
+//! assert_eq!(Some(&2), merge.value.get("theme"));
+//! assert_eq!(1, merge.conflicts.len());
+//! assert_eq!(r#".["theme"]"#, merge.conflicts[0].path.to_string());
+//! ```
+//!
+//! `Vec`s are merged element by element as long as neither side changes their length, and as a
+//! whole otherwise.
+
+use crate::diff::{Atomic, Changeable, Diffable};
+use crate::path::{Path, PathSegment};
+use crate::MySerialize;
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hash};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Wrapping,
+};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Value taking part in a [`Conflict`], which can be printed or downcast to its concrete type.
+pub trait ConflictValue: Debug + Any {
+    /// Returns the value as [`Any`], for downcasting.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Debug + Any> ConflictValue for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Part of a value that both sides changed in different ways.
+#[derive(Debug)]
+pub struct Conflict {
+    /// Location of the conflicting part.
+    pub path: Path,
+    /// Our value, or `None` if we removed it.
+    pub ours: Option<Box<dyn ConflictValue>>,
+    /// Their value, or `None` if they removed it.
+    pub theirs: Option<Box<dyn ConflictValue>>,
+}
+
+/// Result of [`merge3`].
+#[derive(Debug)]
+pub struct Merge<T> {
+    /// Merged value, keeping our side of every conflict.
+    pub value: T,
+    /// Conflicts found while merging, in a stable order. Map entries are visited by key, with
+    /// `HashMap` keys ordered by their `Debug` form.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl<T> Merge<T> {
+    /// Returns `true` if both sides were merged without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Keeps track of the current path and of the conflicts found during a merge.
+#[derive(Debug, Default)]
+pub struct Merger {
+    path: Vec<PathSegment>,
+    conflicts: Vec<Conflict>,
+}
+
+impl Merger {
+    /// Runs `f` with `segment` appended to the current path.
+    pub fn scoped<R>(&mut self, segment: PathSegment, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.path.push(segment);
+        let out = f(self);
+        self.path.pop();
+        out
+    }
+
+    /// Records a conflict at the current path.
+    pub fn conflict<T>(&mut self, ours: Option<&T>, theirs: Option<&T>)
+    where
+        T: Clone + Debug + 'static,
+    {
+        let boxed = |value: &T| Box::new(value.clone()) as Box<dyn ConflictValue>;
+        self.conflicts.push(Conflict {
+            path: Path(self.path.clone()),
+            ours: ours.map(boxed),
+            theirs: theirs.map(boxed),
+        });
+    }
+
+    /// Merges a value as a whole, using `changed` to tell whether two values differ.
+    pub fn merge_leaf_by<T>(
+        &mut self,
+        ours: &mut T,
+        base: &T,
+        theirs: &T,
+        changed: impl Fn(&T, &T) -> bool,
+    ) where
+        T: Clone + Debug + 'static,
+    {
+        if !changed(base, theirs) || !changed(ours, theirs) {
+            return;
+        }
+        if changed(base, ours) {
+            self.conflict(Some(&*ours), Some(theirs));
+        } else {
+            *ours = theirs.clone();
+        }
+    }
+
+    /// Merges a value as a whole, comparing values through their diff.
+    pub fn merge_leaf<T>(&mut self, ours: &mut T, base: &T, theirs: &T)
+    where
+        T: Diffable + Clone + Debug + 'static,
+    {
+        self.merge_leaf_by(ours, base, theirs, |a, b| a.diff(b).is_changed());
+    }
+
+    /// Merges a value that may be missing on any side, such as a map entry. Returns the new value
+    /// for our side if it has to be replaced.
+    pub(crate) fn merge_present<T>(
+        &mut self,
+        ours: Option<&mut T>,
+        base: Option<&T>,
+        theirs: Option<&T>,
+    ) -> Option<Option<T>>
+    where
+        T: Mergeable + Clone + Debug + 'static,
+    {
+        let ours = match (ours, base, theirs) {
+            (Some(ours), Some(base), Some(theirs)) => {
+                ours.merge(base, theirs, self);
+                return None;
+            }
+            (ours, _, _) => ours.map(|ours| &*ours),
+        };
+
+        let changed = |a: Option<&T>, b: Option<&T>| match (a, b) {
+            (Some(a), Some(b)) => a.diff(b).is_changed(),
+            (a, b) => a.is_some() != b.is_some(),
+        };
+        if !changed(base, theirs) || !changed(ours, theirs) {
+            return None;
+        }
+        if changed(base, ours) {
+            self.conflict(ours, theirs);
+            None
+        } else {
+            Some(theirs.cloned())
+        }
+    }
+}
+
+/// Trait for types whose concurrent changes can be merged.
+pub trait Mergeable: Diffable {
+    /// Merges the changes from `base` to `theirs` into `self`, our changed version of `base`.
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger);
+}
+
+/// Merges the changes from `base` to `ours` and from `base` to `theirs`.
+pub fn merge3<T: Mergeable + Clone>(base: &T, ours: &T, theirs: &T) -> Merge<T> {
+    let mut merger = Merger::default();
+    let mut value = ours.clone();
+    value.merge(base, theirs, &mut merger);
+    Merge {
+        value,
+        conflicts: merger.conflicts,
+    }
+}
+
+/// Macro to implement the `Mergeable` trait for types that are merged as a whole.
+#[doc(hidden)]
+macro_rules! impl_leaves {
+    ($($ty:ty),*) => {
+        $(
+            impl Mergeable for $ty {
+                fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+                    merger.merge_leaf(self, base, theirs);
+                }
+            }
+        )*
+    };
+}
+
+impl_leaves!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    bool,
+    char,
+    (),
+    f32,
+    f64
+);
+impl_leaves!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+impl_leaves!(String, Box<str>, Rc<str>, Arc<str>);
+
+/// Borrowed strings can't be held by a [`Conflict`], so they are merged as a `String`.
+impl Mergeable for Cow<'_, str> {
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        let mut ours = self.to_string();
+        merger.merge_leaf(&mut ours, &base.to_string(), &theirs.to_string());
+        if ours != **self {
+            *self = Cow::Owned(ours);
+        }
+    }
+}
+
+impl<T> Mergeable for Wrapping<T>
+where
+    T: Mergeable,
+{
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        self.0.merge(&base.0, &theirs.0, merger);
+    }
+}
+
+impl<T> Mergeable for Atomic<T>
+where
+    T: PartialEq + Clone + Debug + 'static,
+    for<'de> T: MySerialize<'de>,
+{
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        merger.merge_leaf(self, base, theirs);
+    }
+}
+
+impl<T> Mergeable for Option<T>
+where
+    T: Mergeable + Clone + Debug + 'static,
+    for<'de> T: MySerialize<'de>,
+{
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        if let Some(value) = merger.merge_present(self.as_mut(), base.as_ref(), theirs.as_ref()) {
+            *self = value;
+        }
+    }
+}
+
+impl<T> Mergeable for Vec<T>
+where
+    T: Mergeable + Clone + Debug + PartialEq + 'static,
+    for<'de> T: MySerialize<'de>,
+{
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        if self.len() != base.len() || theirs.len() != base.len() {
+            merger.merge_leaf(self, base, theirs);
+            return;
+        }
+
+        for (i, ours) in self.iter_mut().enumerate() {
+            merger.scoped(PathSegment::Index(i), |merger| {
+                ours.merge(&base[i], &theirs[i], merger)
+            });
+        }
+    }
+}
+
+impl<K, T, S> Mergeable for HashMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: Mergeable + Debug + Clone + 'static,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        // Keys are merged in the order of their `Debug` form, which paths use as well, so
+        // conflicts are reported in the same order on every run.
+        let added = theirs.keys().filter(|k| !base.contains_key(*k));
+        let mut keys: Vec<_> = base.keys().chain(added).collect();
+        keys.sort_by_cached_key(|k| format!("{k:?}"));
+
+        for k in keys {
+            let value = merger.scoped(PathSegment::key(k), |merger| {
+                merger.merge_present(self.get_mut(k), base.get(k), theirs.get(k))
+            });
+            match value {
+                Some(Some(value)) => {
+                    self.insert(k.clone(), value);
+                }
+                Some(None) => {
+                    self.remove(k);
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+impl<K, T> Mergeable for BTreeMap<K, T>
+where
+    K: Hash + Eq + Ord + Debug + Clone,
+    T: Mergeable + Debug + Clone + 'static,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+{
+    fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut Merger) {
+        let keys: BTreeSet<_> = base.keys().chain(theirs.keys()).collect();
+
+        for k in keys {
+            let value = merger.scoped(PathSegment::key(k), |merger| {
+                merger.merge_present(self.get_mut(k), base.get(k), theirs.get(k))
+            });
+            match value {
+                Some(Some(value)) => {
+                    self.insert(k.clone(), value);
+                }
+                Some(None) => {
+                    self.remove(k);
+                }
+                None => {}
+            }
+        }
+    }
+}
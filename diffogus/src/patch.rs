@@ -0,0 +1,335 @@
+//! # Applying diffs
+//!
+//! [`Patchable`] is the inverse of [`Diffable::diff`]: given `a` and the diff between `a` and `b`,
+//! it turns `a` into `b`. This makes it possible to ship serialized diffs around and replay them.
+//!
+//! ```rust
+//! use diffogus::diff::Diffable;
+//! use diffogus::patch::Patchable;
+//!
+//! let a = vec![1, 2, 3];
+//! let b = vec![1, 5];
+//! let diff = a.diff(&b);
+//!
+//! let mut patched = a.clone();
+//! patched.apply(&diff).unwrap();
+//! assert_eq!(b, patched);
+//! ```
+//!
+//! Patching is strict: old values recorded in the diff have to match the patched value, otherwise
+//! [`PatchError`] is returned. If that happens the value may be left partially patched, though
+//! collections never lose items the diff doesn't remove.
+//!
+//! Shared references, `str` and slices can't be modified in place and don't implement
+//! [`Patchable`].
+
+use crate::diff::{
+    Atomic, BTreeMapDiff, Changeable, CollectionDiffEntry, Diffable, HashMapDiff, OptionDiff,
+    PrimitiveDiff, VecDiff,
+};
+use crate::MySerialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Wrapping,
+};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Error returned when a diff doesn't apply to a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatchError {
+    /// The value doesn't match the old value recorded in the diff.
+    Mismatch,
+    /// The diff changes or removes an entry that doesn't exist.
+    Missing,
+    /// The diff adds an entry that already exists.
+    Exists,
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mismatch => f.write_str("value doesn't match the old value of the diff"),
+            Self::Missing => f.write_str("diff refers to an entry that doesn't exist"),
+            Self::Exists => f.write_str("diff adds an entry that already exists"),
+        }
+    }
+}
+
+impl Error for PatchError {}
+
+/// Trait for types that can be turned into another value by applying a diff.
+pub trait Patchable: Diffable {
+    /// Applies `diff` to `self`, so that `a.apply(&a.diff(&b))` turns `a` into `b`.
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError>;
+}
+
+/// Checks that `value` matches the `old` value recorded in a diff.
+fn expect_old<T: Diffable + ?Sized>(value: &T, old: &T) -> Result<(), PatchError> {
+    if value.diff(old).is_changed() {
+        Err(PatchError::Mismatch)
+    } else {
+        Ok(())
+    }
+}
+
+fn apply_primitive<T>(value: &mut T, diff: &PrimitiveDiff<T>) -> Result<(), PatchError>
+where
+    T: Diffable + Clone,
+{
+    if let PrimitiveDiff::Changed { old, new } = diff {
+        expect_old(value, old)?;
+        *value = new.clone();
+    }
+    Ok(())
+}
+
+/// Macro to implement the `Patchable` trait for types diffed as [`PrimitiveDiff`].
+#[doc(hidden)]
+macro_rules! impl_primitives {
+    ($($ty:ty),*) => {
+        $(
+            impl Patchable for $ty {
+                fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+                    apply_primitive(self, diff)
+                }
+            }
+        )*
+    };
+}
+
+impl_primitives!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    bool,
+    char,
+    (),
+    f32,
+    f64,
+    String
+);
+impl_primitives!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+
+/// Macro to implement the `Patchable` trait for owning string types, which are replaced by a new
+/// allocation holding the patched string.
+#[doc(hidden)]
+macro_rules! impl_strs {
+    ($($ty:ty),*) => {
+        $(
+            impl Patchable for $ty {
+                fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+                    if let PrimitiveDiff::Changed { old, new } = diff {
+                        expect_old(&**self, old.as_str())?;
+                        *self = new.clone().into();
+                    }
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_strs!(Box<str>, Rc<str>, Arc<str>, Cow<'_, str>);
+
+impl<T> Patchable for Wrapping<T>
+where
+    T: Patchable,
+{
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        self.0.apply(diff)
+    }
+}
+
+impl<T> Atomic<T>
+where
+    T: PartialEq + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    /// Applies a diff computed by [`Atomic::diff_values`] to an unwrapped value.
+    pub fn apply_value(value: &mut T, diff: &PrimitiveDiff<Self>) -> Result<(), PatchError> {
+        if let PrimitiveDiff::Changed { old, new } = diff {
+            if *value != old.0 {
+                return Err(PatchError::Mismatch);
+            }
+            *value = new.0.clone();
+        }
+        Ok(())
+    }
+}
+
+impl<T> Patchable for Atomic<T>
+where
+    T: PartialEq + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        apply_primitive(self, diff)
+    }
+}
+
+impl<T> Patchable for Option<T>
+where
+    T: Patchable + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        match (diff, self.as_mut()) {
+            (OptionDiff::Unchanged, _) => {}
+            (OptionDiff::Changed(diff), Some(value)) => value.apply(diff)?,
+            (OptionDiff::Changed(_), None) => return Err(PatchError::Missing),
+            (OptionDiff::Removed(old), Some(value)) => {
+                expect_old(value, old)?;
+                *self = None;
+            }
+            (OptionDiff::Removed(_), None) => return Err(PatchError::Missing),
+            (OptionDiff::Added(new), None) => *self = Some(new.clone()),
+            (OptionDiff::Added(_), Some(_)) => return Err(PatchError::Exists),
+        }
+        Ok(())
+    }
+}
+
+impl<T> Patchable for Vec<T>
+where
+    T: Patchable + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        let VecDiff(entries) = diff;
+        let olds: Vec<_> = entries
+            .iter()
+            .filter(|entry| !matches!(entry, CollectionDiffEntry::Added(_)))
+            .collect();
+        if olds.len() > self.len() {
+            return Err(PatchError::Missing);
+        } else if olds.len() < self.len() {
+            return Err(PatchError::Mismatch);
+        }
+
+        // Everything that can fail happens in place, so a failed patch never drops items.
+        for (value, entry) in self.iter().zip(&olds) {
+            if let CollectionDiffEntry::Removed(old) = entry {
+                expect_old(value, old)?;
+            }
+        }
+        for (value, entry) in self.iter_mut().zip(&olds) {
+            if let CollectionDiffEntry::Changed(diff) = entry {
+                value.apply(diff)?;
+            }
+        }
+
+        let mut old = mem::take(self).into_iter();
+        for entry in entries {
+            match entry {
+                CollectionDiffEntry::Added(new) => self.push(new.clone()),
+                CollectionDiffEntry::Removed(_) => {
+                    old.next();
+                }
+                CollectionDiffEntry::Changed(_) | CollectionDiffEntry::Unchanged => {
+                    self.extend(old.next())
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<K, T, S> Patchable for HashMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: Patchable + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        let HashMapDiff(entries) = diff;
+
+        for (k, entry) in entries {
+            match entry {
+                CollectionDiffEntry::Unchanged if !self.contains_key(k) => {
+                    return Err(PatchError::Missing)
+                }
+                CollectionDiffEntry::Unchanged => {}
+                CollectionDiffEntry::Changed(diff) => {
+                    self.get_mut(k).ok_or(PatchError::Missing)?.apply(diff)?
+                }
+                CollectionDiffEntry::Removed(old) => {
+                    expect_old(self.get(k).ok_or(PatchError::Missing)?, old)?;
+                    self.remove(k);
+                }
+                CollectionDiffEntry::Added(_) if self.contains_key(k) => {
+                    return Err(PatchError::Exists)
+                }
+                CollectionDiffEntry::Added(new) => {
+                    self.insert(k.clone(), new.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<K, T> Patchable for BTreeMap<K, T>
+where
+    K: Hash + Eq + Ord + Debug + Clone,
+    T: Patchable + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+{
+    fn apply(&mut self, diff: &Self::Repr) -> Result<(), PatchError> {
+        let BTreeMapDiff(entries) = diff;
+
+        for (k, entry) in entries {
+            match entry {
+                CollectionDiffEntry::Unchanged if !self.contains_key(k) => {
+                    return Err(PatchError::Missing)
+                }
+                CollectionDiffEntry::Unchanged => {}
+                CollectionDiffEntry::Changed(diff) => {
+                    self.get_mut(k).ok_or(PatchError::Missing)?.apply(diff)?
+                }
+                CollectionDiffEntry::Removed(old) => {
+                    expect_old(self.get(k).ok_or(PatchError::Missing)?, old)?;
+                    self.remove(k);
+                }
+                CollectionDiffEntry::Added(_) if self.contains_key(k) => {
+                    return Err(PatchError::Exists)
+                }
+                CollectionDiffEntry::Added(new) => {
+                    self.insert(k.clone(), new.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+//! # Paths into values
+//!
+//! A [`Path`] points at a part of a value, such as a struct field, a `Vec` element or a map entry.
+//! It is displayed in the style of `jq`, e.g. `.users["joe"].roles[0]` or `.[1]`, with `.` for the
+//! value itself.
+
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// A single step of a [`Path`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// Field of a struct or of an enum variant, named by its index for tuple fields.
+    Field(&'static str),
+    /// Element of a `Vec` or an array.
+    Index(usize),
+    /// Entry of a map, holding the `Debug` representation of its key.
+    Key(String),
+}
+
+impl PathSegment {
+    /// Segment for the map entry with key `key`.
+    pub fn key<K: Debug + ?Sized>(key: &K) -> Self {
+        Self::Key(format!("{key:?}"))
+    }
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field(name) => write!(f, ".{name}"),
+            Self::Index(index) => write!(f, "[{index}]"),
+            Self::Key(key) => write!(f, "[{key}]"),
+        }
+    }
+}
+
+/// Location of a part of a value, as a list of steps from the value itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path(pub Vec<PathSegment>);
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !matches!(self.0.first(), Some(PathSegment::Field(_))) {
+            f.write_str(".")?;
+        }
+        self.0
+            .iter()
+            .try_for_each(|segment| Display::fmt(segment, f))
+    }
+}
@@ -0,0 +1,303 @@
+//! # Rebasing diffs
+//!
+//! [`rebase`] transforms a diff so that it applies on top of another diff computed from the same
+//! base, as needed when a change made against an older version arrives after a concurrent one.
+//! `Vec` positions are shifted past items inserted or removed by the other diff, and parts that
+//! can't be carried over are dropped from the result and reported as [`Rejection`]s. When both
+//! diffs change the same part, the other diff wins.
+//!
+//! ```rust
+//! use diffogus::diff::Diffable;
+//! use diffogus::patch::Patchable;
+//! use diffogus::rebase::rebase;
+//!
+//! let base = vec![1, 2, 3];
+//! let ours = base.diff(&vec![1, 2, 3, 4]);
+//! let theirs = base.diff(&vec![1]);
+//!
+//! let rebased = rebase(ours, &theirs);
+//! assert!(rebased.is_clean());
+//!
+//! let mut value = vec![1];
+//! value.apply(&rebased.diff).unwrap();
+//! assert_eq!(vec![1, 4], value);
+//! ```
+
+use crate::diff::{
+    BTreeMapDiff, Changeable, CollectionDiffEntry, Diffable, HashMapDiff, OptionDiff,
+    PrimitiveDiff, VecDiff,
+};
+use crate::path::{Path, PathSegment};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Why a part of a diff couldn't be rebased.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RejectReason {
+    /// Both diffs change the same part in different ways.
+    Conflict,
+    /// The part was removed by the other diff.
+    Removed,
+    /// The diffs don't start from the same value.
+    Mismatch,
+}
+
+/// Part of a diff that was dropped while rebasing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rejection {
+    /// Location of the dropped part in the base value.
+    pub path: Path,
+    /// Why it was dropped.
+    pub reason: RejectReason,
+}
+
+/// Result of [`rebase`].
+#[derive(Debug)]
+pub struct Rebased<D> {
+    /// Rebased diff, which applies to the value produced by the other diff.
+    pub diff: D,
+    /// Parts of the original diff that were dropped.
+    pub rejected: Vec<Rejection>,
+}
+
+impl<D> Rebased<D> {
+    /// Returns `true` if the whole diff was rebased.
+    pub fn is_clean(&self) -> bool {
+        self.rejected.is_empty()
+    }
+}
+
+/// Keeps track of the current path and of the rejected parts during a rebase.
+#[derive(Debug, Default)]
+pub struct Rebaser {
+    path: Vec<PathSegment>,
+    rejected: Vec<Rejection>,
+}
+
+impl Rebaser {
+    /// Runs `f` with `segment` appended to the current path.
+    pub fn scoped<R>(&mut self, segment: PathSegment, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.path.push(segment);
+        let out = f(self);
+        self.path.pop();
+        out
+    }
+
+    /// Records that the part at the current path was dropped.
+    pub fn reject(&mut self, reason: RejectReason) {
+        self.rejected.push(Rejection {
+            path: Path(self.path.clone()),
+            reason,
+        });
+    }
+}
+
+/// Trait for diff representations that can be rebased onto a concurrent diff.
+pub trait Rebasable: Sized {
+    /// Transforms `self` so that it applies after `onto`, both starting from the same value.
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self;
+}
+
+/// Rebases `diff` onto `onto`, a concurrent diff from the same base.
+pub fn rebase<D: Rebasable>(diff: D, onto: &D) -> Rebased<D> {
+    let mut rebaser = Rebaser::default();
+    let diff = diff.rebase(onto, &mut rebaser);
+    Rebased {
+        diff,
+        rejected: rebaser.rejected,
+    }
+}
+
+impl<T: Diffable> Rebasable for PrimitiveDiff<T> {
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        match (self, onto) {
+            (diff, Self::Unchanged) => diff,
+            (Self::Unchanged, _) => Self::Unchanged,
+            (Self::Changed { new, .. }, Self::Changed { new: onto_new, .. }) => {
+                if new.diff(onto_new).is_changed() {
+                    rebaser.reject(RejectReason::Conflict);
+                }
+                Self::Unchanged
+            }
+        }
+    }
+}
+
+/// Rebases a change to a single item. `None` means the item no longer exists, so the entry has
+/// to be left out.
+fn rebase_entry<T>(
+    entry: CollectionDiffEntry<T>,
+    onto: &CollectionDiffEntry<T>,
+    rebaser: &mut Rebaser,
+) -> Option<CollectionDiffEntry<T>>
+where
+    T: Diffable,
+    <T as Diffable>::Repr: Rebasable,
+{
+    use CollectionDiffEntry::*;
+
+    match (entry, onto) {
+        (entry, Unchanged) => Some(entry),
+        (Unchanged, Removed(_)) | (Removed(_), Removed(_)) => None,
+        (Changed(_), Removed(_)) => {
+            rebaser.reject(RejectReason::Removed);
+            None
+        }
+        (Unchanged, _) => Some(Unchanged),
+        (Changed(diff), Changed(onto)) => match diff.rebase(onto, rebaser) {
+            diff if diff.is_changed() => Some(Changed(diff)),
+            _ => Some(Unchanged),
+        },
+        (Added(value), Added(onto)) => {
+            if value.diff(onto).is_changed() {
+                rebaser.reject(RejectReason::Conflict);
+            }
+            Some(Unchanged)
+        }
+        (Removed(_), Changed(_)) => {
+            rebaser.reject(RejectReason::Conflict);
+            Some(Unchanged)
+        }
+        (Added(_), _) | (_, Added(_)) => {
+            rebaser.reject(RejectReason::Mismatch);
+            Some(Unchanged)
+        }
+    }
+}
+
+impl<T> Rebasable for OptionDiff<T>
+where
+    T: Diffable,
+    <T as Diffable>::Repr: Rebasable,
+{
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        match (self, onto) {
+            (diff, Self::Unchanged) => diff,
+            (Self::Unchanged, _) | (Self::Removed(_), Self::Removed(_)) => Self::Unchanged,
+            (Self::Changed(diff), Self::Changed(onto)) => match diff.rebase(onto, rebaser) {
+                diff if diff.is_changed() => Self::Changed(diff),
+                _ => Self::Unchanged,
+            },
+            (Self::Changed(_), Self::Removed(_)) => {
+                rebaser.reject(RejectReason::Removed);
+                Self::Unchanged
+            }
+            (Self::Added(value), Self::Added(onto)) => {
+                if value.diff(onto).is_changed() {
+                    rebaser.reject(RejectReason::Conflict);
+                }
+                Self::Unchanged
+            }
+            (Self::Removed(_), Self::Changed(_)) => {
+                rebaser.reject(RejectReason::Conflict);
+                Self::Unchanged
+            }
+            _ => {
+                rebaser.reject(RejectReason::Mismatch);
+                Self::Unchanged
+            }
+        }
+    }
+}
+
+impl<T> Rebasable for VecDiff<T>
+where
+    T: Diffable,
+    <T as Diffable>::Repr: Rebasable,
+{
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        let mut entries = self.0.into_iter().peekable();
+        let mut onto = onto.0.iter().peekable();
+        let mut out = vec![];
+        let mut index = 0;
+
+        loop {
+            // Items inserted by the other diff go first and are left untouched.
+            if onto
+                .next_if(|o| matches!(o, CollectionDiffEntry::Added(_)))
+                .is_some()
+            {
+                out.push(CollectionDiffEntry::Unchanged);
+                continue;
+            }
+            if let Some(added) = entries.next_if(|e| matches!(e, CollectionDiffEntry::Added(_))) {
+                out.push(added);
+                continue;
+            }
+
+            match (entries.next(), onto.next()) {
+                (Some(entry), Some(onto)) => {
+                    let entry = rebaser.scoped(PathSegment::Index(index), |rebaser| {
+                        rebase_entry(entry, onto, rebaser)
+                    });
+                    out.extend(entry);
+                    index += 1;
+                }
+                (None, None) => break,
+                _ => {
+                    rebaser.reject(RejectReason::Mismatch);
+                    return Self::default();
+                }
+            }
+        }
+
+        Self(out)
+    }
+}
+
+/// Rebases the entries of a map diff. Entries missing from `onto` count as unchanged.
+pub(crate) fn rebase_map<'a, K, T, M>(
+    entries: M,
+    onto: impl Fn(&K) -> Option<&'a CollectionDiffEntry<T>>,
+    rebaser: &mut Rebaser,
+) -> M
+where
+    K: Debug,
+    T: Diffable + 'a,
+    <T as Diffable>::Repr: Rebasable,
+    M: IntoIterator<Item = (K, CollectionDiffEntry<T>)> + FromIterator<(K, CollectionDiffEntry<T>)>,
+{
+    entries
+        .into_iter()
+        .filter_map(|(k, entry)| {
+            let entry = match onto(&k) {
+                Some(onto) => rebaser.scoped(PathSegment::key(&k), |rebaser| {
+                    rebase_entry(entry, onto, rebaser)
+                })?,
+                None => entry,
+            };
+            Some((k, entry))
+        })
+        .collect()
+}
+
+impl<K, T> Rebasable for HashMapDiff<K, T>
+where
+    K: Hash + Eq + Debug,
+    T: Diffable,
+    <T as Diffable>::Repr: Rebasable,
+{
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        Self(rebase_map::<_, _, HashMap<_, _>>(
+            self.0,
+            |k| onto.0.get(k),
+            rebaser,
+        ))
+    }
+}
+
+impl<K, T> Rebasable for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord + Debug,
+    T: Diffable,
+    <T as Diffable>::Repr: Rebasable,
+{
+    fn rebase(self, onto: &Self, rebaser: &mut Rebaser) -> Self {
+        Self(rebase_map::<_, _, BTreeMap<_, _>>(
+            self.0,
+            |k| onto.0.get(k),
+            rebaser,
+        ))
+    }
+}
@@ -0,0 +1,373 @@
+//! # Similarity and distance
+//!
+//! [`Similar`] scores how close two values are, e.g. to pick the candidate closest to a reference
+//! value. [`similarity`] ranges from `0.0` for values with nothing in common to `1.0` for equal
+//! values, while [`distance`] counts the edits turning one value into the other.
+//!
+//! ```rust
+//! use diffogus::similarity::{distance, similarity};
+//!
+//! assert_eq!(1.0, similarity("kitten", "kitten"));
+//! assert_eq!(0.5, similarity("kitten", "mitt"));
+//! assert_eq!(3.0, distance("kitten", "mitt"));
+//!
+//! let reference = vec![1, 2, 3];
+//! let candidates = [vec![1, 5], vec![3, 2, 1], vec![1, 2, 4]];
+//! let closest = candidates
+//!     .iter()
+//!     .max_by(|a, b| similarity(*a, &reference).total_cmp(&similarity(*b, &reference)));
+//! assert_eq!(Some(&vec![1, 2, 4]), closest);
+//! ```
+//!
+//! Numbers, booleans and [`Atomic`] values are either equal or completely different, while
+//! strings are compared character by character. `Vec`s line up their items like
+//! [`VecAlgorithm::Lcs`], so an insertion only costs the inserted item, and maps compare entry
+//! by entry. Scores are averaged over all lined up items or keys,
+//! and every item or key without a counterpart counts as one edit. Lining up `Vec`s takes
+//! quadratic time.
+
+use crate::diff::{align, Atomic, Changeable, Diffable, Step, VecAlgorithm};
+use crate::MySerialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hash};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Wrapping,
+};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Trait for types whose values can be scored by how close they are to each other.
+pub trait Similar: Diffable {
+    /// Returns how similar `self` and `other` are, from `0.0` for nothing in common to `1.0` for
+    /// equal values.
+    fn similarity(&self, other: &Self) -> f64;
+
+    /// Returns the number of edits turning `self` into `other`, `0.0` for equal values.
+    fn distance(&self, other: &Self) -> f64;
+}
+
+/// Returns how similar `a` and `b` are, from `0.0` to `1.0`.
+pub fn similarity<T: Similar + ?Sized>(a: &T, b: &T) -> f64 {
+    a.similarity(b)
+}
+
+/// Returns the number of edits turning `a` into `b`.
+pub fn distance<T: Similar + ?Sized>(a: &T, b: &T) -> f64 {
+    a.distance(b)
+}
+
+/// Similarity of two values compared as a whole.
+fn leaf_similarity<T: Diffable + ?Sized>(a: &T, b: &T) -> f64 {
+    if a.diff(b).is_changed() {
+        0.0
+    } else {
+        1.0
+    }
+}
+
+/// Number of characters to insert, remove or replace to turn `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == *b {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Character level similarity of two strings.
+pub(crate) fn str_similarity(a: &str, b: &str) -> f64 {
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / len as f64
+}
+
+/// Character level distance between two strings.
+pub(crate) fn str_distance(a: &str, b: &str) -> f64 {
+    levenshtein(a, b) as f64
+}
+
+/// Average similarity of the `pairs` of matching items, out of `total` items including the ones
+/// without a match.
+pub(crate) fn similarity_of<'a, T>(pairs: &[(&'a T, &'a T)], total: usize) -> f64
+where
+    T: Similar + ?Sized + 'a,
+{
+    if total == 0 {
+        return 1.0;
+    }
+    pairs.iter().map(|(a, b)| a.similarity(b)).sum::<f64>() / total as f64
+}
+
+/// Distance between the `pairs` of matching items, plus one edit for each of the `unmatched`
+/// items.
+pub(crate) fn distance_of<'a, T>(pairs: &[(&'a T, &'a T)], unmatched: usize) -> f64
+where
+    T: Similar + ?Sized + 'a,
+{
+    pairs.iter().map(|(a, b)| a.distance(b)).sum::<f64>() + unmatched as f64
+}
+
+/// Macro to implement the `Similar` trait for types compared as a whole.
+#[doc(hidden)]
+macro_rules! impl_leaves {
+    ($($ty:ty),*) => {
+        $(
+            impl Similar for $ty {
+                fn similarity(&self, other: &Self) -> f64 {
+                    leaf_similarity(self, other)
+                }
+
+                fn distance(&self, other: &Self) -> f64 {
+                    1.0 - leaf_similarity(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_leaves!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    bool,
+    char,
+    (),
+    f32,
+    f64
+);
+impl_leaves!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+
+impl Similar for String {
+    fn similarity(&self, other: &Self) -> f64 {
+        str_similarity(self, other)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        str_distance(self, other)
+    }
+}
+
+impl Similar for str {
+    fn similarity(&self, other: &Self) -> f64 {
+        str_similarity(self, other)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        str_distance(self, other)
+    }
+}
+
+/// Macro to implement the `Similar` trait for owning string types, compared like `str`.
+#[doc(hidden)]
+macro_rules! impl_strs {
+    ($($ty:ty),*) => {
+        $(
+            impl Similar for $ty {
+                fn similarity(&self, other: &Self) -> f64 {
+                    str_similarity(self, other)
+                }
+
+                fn distance(&self, other: &Self) -> f64 {
+                    str_distance(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_strs!(Box<str>, Rc<str>, Arc<str>, Cow<'_, str>);
+
+impl<T> Similar for Wrapping<T>
+where
+    T: Similar,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        self.0.similarity(&other.0)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        self.0.distance(&other.0)
+    }
+}
+
+impl<T> Similar for &T
+where
+    T: Similar + ?Sized,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        (**self).similarity(*other)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        (**self).distance(*other)
+    }
+}
+
+impl<T> Similar for Atomic<T>
+where
+    T: PartialEq + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        leaf_similarity(self, other)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        1.0 - leaf_similarity(self, other)
+    }
+}
+
+impl<T> Similar for Option<T>
+where
+    T: Similar + Clone + Debug,
+    for<'de> T: MySerialize<'de>,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        match (self, other) {
+            (Some(a), Some(b)) => a.similarity(b),
+            (None, None) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        match (self, other) {
+            (Some(a), Some(b)) => a.distance(b),
+            (None, None) => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Lines up the items like [`VecAlgorithm::Lcs`], returning the pairs of lined up items and the
+/// number of all items.
+fn aligned_pairs<'a, T: Diffable>(a: &'a [T], b: &'a [T]) -> (Vec<(&'a T, &'a T)>, usize) {
+    let steps = align(a, b, VecAlgorithm::Lcs, |a, b| !a.diff(b).is_changed());
+    let pairs = steps
+        .iter()
+        .filter_map(|step| match *step {
+            Step::Equal(i, j) | Step::Pair(i, j) => Some((&a[i], &b[j])),
+            Step::Removed(_) | Step::Added(_) => None,
+        })
+        .collect();
+    (pairs, steps.len())
+}
+
+impl<T> Similar for [T]
+where
+    T: Similar + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        let (pairs, total) = aligned_pairs(self, other);
+        similarity_of(&pairs, total)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        let (pairs, total) = aligned_pairs(self, other);
+        distance_of(&pairs, total - pairs.len())
+    }
+}
+
+impl<T> Similar for Vec<T>
+where
+    T: Similar + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        self[..].similarity(&other[..])
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        self[..].distance(&other[..])
+    }
+}
+
+/// Pairs up the entries with the same key, returning the pairs and the number of all keys.
+pub(crate) fn key_pairs<'a, K: 'a, T: 'a>(
+    a: impl ExactSizeIterator<Item = (&'a K, &'a T)>,
+    b: &impl Fn(&K) -> Option<&'a T>,
+    b_len: usize,
+) -> (Vec<(&'a T, &'a T)>, usize) {
+    let a_len = a.len();
+    let pairs: Vec<_> = a.filter_map(|(k, a)| b(k).map(|b| (a, b))).collect();
+    let total = a_len + b_len - pairs.len();
+    (pairs, total)
+}
+
+impl<K, T, S> Similar for HashMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: Similar + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        let (pairs, total) = key_pairs(self.iter(), &|k| other.get(k), other.len());
+        similarity_of(&pairs, total)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        let (pairs, total) = key_pairs(self.iter(), &|k| other.get(k), other.len());
+        distance_of(&pairs, total - pairs.len())
+    }
+}
+
+impl<K, T> Similar for BTreeMap<K, T>
+where
+    K: Hash + Eq + Ord + Debug + Clone,
+    T: Similar + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+{
+    fn similarity(&self, other: &Self) -> f64 {
+        let (pairs, total) = key_pairs(self.iter(), &|k| other.get(k), other.len());
+        similarity_of(&pairs, total)
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        let (pairs, total) = key_pairs(self.iter(), &|k| other.get(k), other.len());
+        distance_of(&pairs, total - pairs.len())
+    }
+}
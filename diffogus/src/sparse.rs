@@ -0,0 +1,676 @@
+//! # Sparse diffs
+//!
+//! The diffs of maps and `Vec`s hold an entry for every key or item, including the unchanged
+//! ones. [`SparseDiffable::diff_sparse`] computes diffs that only store the changes, so a single
+//! changed key in a large map allocates a single entry.
+//!
+//! ```rust
+//! use diffogus::diff::{CollectionDiffEntry, PrimitiveDiff};
+//! use diffogus::sparse::SparseDiffable;
+//! use std::collections::HashMap;
+//!
+//! let a: HashMap<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+//! let mut b = a.clone();
+//! b.insert(7, 0);
+//!
+//! let diff = a.diff_sparse(&b);
+//! assert_eq!(1, diff.len());
+//! assert_eq!(
+//!     Some(&CollectionDiffEntry::Changed(PrimitiveDiff::Changed { old: 7, new: 0 })),
+//!     diff.get(&7)
+//! );
+//! assert!(diff.is_unchanged_at(&8));
+//! ```
+//!
+//! Sparse diffs serialize only their changes: maps like the regular ones with the unchanged entries
+//! left out, `Vec`s as the number of entries in the regular diff plus the changes by position.
+//! Both can be deserialized from that form or from the regular JSON, which drops the unchanged
+//! entries again. Only the outermost collection is sparse; the diffs of the changed values are the
+//! regular [`Diffable::Repr`].
+//!
+//! Sparse `Vec` diffs convert back into a [`VecDiff`] with `try_from`, which fails with
+//! [`PositionOutOfRange`] if a change lies at or past `len`.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::diff::{
+    align, BTreeMapDiff, Changeable, CollectionDiffEntry, DiffOptions, Diffable, HashMapDiff, Step,
+    VecDiff,
+};
+use crate::path::PathSegment;
+use crate::visit::{walk_map, Walkable, Walker};
+use crate::MySerialize;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{BuildHasher, Hash};
+
+/// Trait for collections that can compute a diff storing only the changes.
+pub trait SparseDiffable: Diffable {
+    /// The type used to represent the changes between two objects.
+    type SparseRepr: Changeable + Debug + for<'de> MySerialize<'de>;
+
+    /// Computes the changes between `self` and `b`, using the default [`DiffOptions`].
+    fn diff_sparse(&self, b: &Self) -> Self::SparseRepr {
+        self.diff_sparse_with(b, &DiffOptions::default())
+    }
+
+    /// Computes the changes between `self` and `b`, configured by `options`.
+    fn diff_sparse_with(&self, b: &Self, options: &DiffOptions) -> Self::SparseRepr;
+}
+
+/// Represents the changed entries between two `HashMap` collections.
+///
+/// With the `serde` feature it serializes like the equivalent [`HashMapDiff`] with the unchanged
+/// entries left out, and deserializes from either form.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "HashMapDiff<K, T>"))]
+pub struct SparseHashMapDiff<K, T>(pub HashMap<K, CollectionDiffEntry<T>>)
+where
+    K: Hash + Eq,
+    T: Diffable;
+
+impl<K, T> SparseHashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+{
+    /// Returns the change of the entry at `key`, or `None` if it is unchanged.
+    pub fn get<Q>(&self, key: &Q) -> Option<&CollectionDiffEntry<T>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get(key)
+    }
+
+    /// Checks if the entry at `key` is unchanged, which includes keys missing from both maps.
+    pub fn is_unchanged_at<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_none_or(|entry| !entry.is_changed())
+    }
+
+    /// Returns the number of changed entries.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks if no entry changed.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the changed entries.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &CollectionDiffEntry<T>)> {
+        self.0.iter()
+    }
+}
+
+impl<K, T> From<HashMapDiff<K, T>> for SparseHashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+{
+    fn from(diff: HashMapDiff<K, T>) -> Self {
+        Self(diff.0.into_iter().filter(|(_, e)| e.is_changed()).collect())
+    }
+}
+
+impl<K, T> Default for SparseHashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+{
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<K, T> PartialEq for SparseHashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable + PartialEq,
+    <T as Diffable>::Repr: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K, T> Eq for SparseHashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable + Eq,
+    <T as Diffable>::Repr: Eq,
+{
+}
+
+impl<K, T> Clone for SparseHashMapDiff<K, T>
+where
+    K: Hash + Eq + Clone,
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K, T> Changeable for SparseHashMapDiff<K, T>
+where
+    K: Hash + Eq,
+    T: Diffable,
+{
+    fn is_changed(&self) -> bool {
+        self.0.values().any(|v| v.is_changed())
+    }
+}
+
+impl<K, T> Walkable for SparseHashMapDiff<K, T>
+where
+    K: Hash + Eq + Debug,
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        walk_map(&self.0, walker);
+    }
+}
+
+impl<K, T, S> SparseDiffable for HashMap<K, T, S>
+where
+    K: Hash + Eq + Debug + Clone,
+    T: Diffable + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+    S: BuildHasher,
+{
+    type SparseRepr = SparseHashMapDiff<K, T>;
+
+    fn diff_sparse_with(&self, b: &Self, options: &DiffOptions) -> Self::SparseRepr {
+        let mut out = HashMap::new();
+
+        for (k, v) in self {
+            match b.get(k) {
+                Some(other) => {
+                    let diff = v.diff_with(other, options);
+                    if diff.is_changed() {
+                        out.insert(k.clone(), CollectionDiffEntry::Changed(diff));
+                    }
+                }
+                None => {
+                    out.insert(k.clone(), CollectionDiffEntry::Removed(v.clone()));
+                }
+            }
+        }
+
+        for (k, v) in b {
+            if !self.contains_key(k) {
+                out.insert(k.clone(), CollectionDiffEntry::Added(v.clone()));
+            }
+        }
+
+        SparseHashMapDiff(out)
+    }
+}
+
+/// Represents the changed entries between two `BTreeMap` collections.
+///
+/// With the `serde` feature it serializes like the equivalent [`BTreeMapDiff`] with the unchanged
+/// entries left out, and deserializes from either form.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "BTreeMapDiff<K, T>"))]
+pub struct SparseBTreeMapDiff<K, T>(pub BTreeMap<K, CollectionDiffEntry<T>>)
+where
+    K: Hash + Eq + Ord,
+    T: Diffable;
+
+impl<K, T> SparseBTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable,
+{
+    /// Returns the change of the entry at `key`, or `None` if it is unchanged.
+    pub fn get<Q>(&self, key: &Q) -> Option<&CollectionDiffEntry<T>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.get(key)
+    }
+
+    /// Checks if the entry at `key` is unchanged, which includes keys missing from both maps.
+    pub fn is_unchanged_at<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_none_or(|entry| !entry.is_changed())
+    }
+
+    /// Returns the number of changed entries.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks if no entry changed.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the changed entries, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &CollectionDiffEntry<T>)> {
+        self.0.iter()
+    }
+}
+
+impl<K, T> From<BTreeMapDiff<K, T>> for SparseBTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable,
+{
+    fn from(diff: BTreeMapDiff<K, T>) -> Self {
+        Self(diff.0.into_iter().filter(|(_, e)| e.is_changed()).collect())
+    }
+}
+
+impl<K, T> Default for SparseBTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable,
+{
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
+
+impl<K, T> PartialEq for SparseBTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable + PartialEq,
+    <T as Diffable>::Repr: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K, T> Eq for SparseBTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable + Eq,
+    <T as Diffable>::Repr: Eq,
+{
+}
+
+impl<K, T> Clone for SparseBTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord + Clone,
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K, T> Changeable for SparseBTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord,
+    T: Diffable,
+{
+    fn is_changed(&self) -> bool {
+        self.0.values().any(|v| v.is_changed())
+    }
+}
+
+impl<K, T> Walkable for SparseBTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord + Debug,
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        walk_map(&self.0, walker);
+    }
+}
+
+impl<K, T> SparseDiffable for BTreeMap<K, T>
+where
+    K: Hash + Eq + Ord + Debug + Clone,
+    T: Diffable + Debug + Clone,
+    for<'de> T: MySerialize<'de>,
+    for<'de> K: MySerialize<'de>,
+{
+    type SparseRepr = SparseBTreeMapDiff<K, T>;
+
+    fn diff_sparse_with(&self, b: &Self, options: &DiffOptions) -> Self::SparseRepr {
+        let mut out = BTreeMap::new();
+
+        for (k, v) in self {
+            match b.get(k) {
+                Some(other) => {
+                    let diff = v.diff_with(other, options);
+                    if diff.is_changed() {
+                        out.insert(k.clone(), CollectionDiffEntry::Changed(diff));
+                    }
+                }
+                None => {
+                    out.insert(k.clone(), CollectionDiffEntry::Removed(v.clone()));
+                }
+            }
+        }
+
+        for (k, v) in b {
+            if !self.contains_key(k) {
+                out.insert(k.clone(), CollectionDiffEntry::Added(v.clone()));
+            }
+        }
+
+        SparseBTreeMapDiff(out)
+    }
+}
+
+/// Represents the changed entries between two `Vec` collections.
+///
+/// Entries are stored by their position in the equivalent [`VecDiff`], which is the index of the
+/// item unless [`VecAlgorithm::Lcs`](crate::diff::VecAlgorithm::Lcs) lined up the items.
+///
+/// With the `serde` feature it serializes as `{"len": .., "changes": {..}}`, leaving the unchanged
+/// entries out like the sparse maps do. It deserializes from that form or from the JSON of the
+/// equivalent [`VecDiff`].
+#[derive(Debug)]
+pub struct SparseVecDiff<T: Diffable> {
+    /// Number of entries in the equivalent [`VecDiff`], changed or not.
+    pub len: usize,
+    /// Changed entries by their position.
+    pub changes: BTreeMap<usize, CollectionDiffEntry<T>>,
+}
+
+impl<T: Diffable> SparseVecDiff<T> {
+    /// Returns the change at `position`, or `None` if it is unchanged.
+    pub fn get(&self, position: usize) -> Option<&CollectionDiffEntry<T>> {
+        self.changes.get(&position)
+    }
+
+    /// Checks if the entry at `position` is unchanged, which includes positions past the end.
+    pub fn is_unchanged_at(&self, position: usize) -> bool {
+        self.get(position).is_none_or(|entry| !entry.is_changed())
+    }
+
+    /// Iterates over the changed entries with their position, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &CollectionDiffEntry<T>)> {
+        self.changes.iter().map(|(i, entry)| (*i, entry))
+    }
+}
+
+impl<T: Diffable> From<VecDiff<T>> for SparseVecDiff<T> {
+    fn from(diff: VecDiff<T>) -> Self {
+        Self {
+            len: diff.0.len(),
+            changes: diff
+                .0
+                .into_iter()
+                .enumerate()
+                .filter(|(_, e)| e.is_changed())
+                .collect(),
+        }
+    }
+}
+
+/// Error returned when a [`SparseVecDiff`] holds a change at or past its `len`, so it has no
+/// equivalent [`VecDiff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PositionOutOfRange {
+    /// Position of the first change outside the diff.
+    pub position: usize,
+    /// Number of entries in the diff.
+    pub len: usize,
+}
+
+impl Display for PositionOutOfRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "change at position {} is out of range for a diff of {} entries",
+            self.position, self.len
+        )
+    }
+}
+
+impl Error for PositionOutOfRange {}
+
+impl<T: Diffable> SparseVecDiff<T> {
+    /// Checks that every change lies within `len`.
+    fn check_positions(&self) -> Result<(), PositionOutOfRange> {
+        match self.changes.range(self.len..).next() {
+            Some((&position, _)) => Err(PositionOutOfRange {
+                position,
+                len: self.len,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Diffable> TryFrom<SparseVecDiff<T>> for VecDiff<T> {
+    type Error = PositionOutOfRange;
+
+    fn try_from(diff: SparseVecDiff<T>) -> Result<Self, Self::Error> {
+        diff.check_positions()?;
+        let mut entries: Vec<_> = (0..diff.len)
+            .map(|_| CollectionDiffEntry::Unchanged)
+            .collect();
+        for (i, entry) in diff.changes {
+            entries[i] = entry;
+        }
+        Ok(VecDiff(entries))
+    }
+}
+
+/// Fails with [`PositionOutOfRange`] instead of writing changes past `len`.
+#[cfg(feature = "serde")]
+impl<T> Serialize for SparseVecDiff<T>
+where
+    T: Diffable + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeStruct};
+
+        self.check_positions().map_err(S::Error::custom)?;
+        let mut state = serializer.serialize_struct("SparseVecDiff", 2)?;
+        state.serialize_field("len", &self.len)?;
+        state.serialize_field("changes", &self.changes)?;
+        state.end()
+    }
+}
+
+/// Accepts the sparse form as well as the JSON of the equivalent [`VecDiff`]. Fails with
+/// [`PositionOutOfRange`] if a change lies at or past `len`.
+#[cfg(feature = "serde")]
+impl<'de, T> Deserialize<'de> for SparseVecDiff<T>
+where
+    T: Diffable + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+        use serde::de::{Error, MapAccess, SeqAccess, Visitor};
+        use std::fmt::Formatter;
+        use std::marker::PhantomData;
+
+        #[derive(Deserialize)]
+        #[serde(bound = "T: Diffable + Deserialize<'de>")]
+        struct Sparse<T: Diffable> {
+            len: usize,
+            changes: BTreeMap<usize, CollectionDiffEntry<T>>,
+        }
+
+        struct SparseVecDiffVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for SparseVecDiffVisitor<T>
+        where
+            T: Diffable + Deserialize<'de>,
+        {
+            type Value = SparseVecDiff<T>;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                f.write_str("a sparse or regular Vec diff")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                Vec::deserialize(SeqAccessDeserializer::new(seq))
+                    .map(|entries| SparseVecDiff::from(VecDiff(entries)))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let Sparse { len, changes } = Sparse::deserialize(MapAccessDeserializer::new(map))?;
+                let diff = SparseVecDiff { len, changes };
+                diff.check_positions().map_err(A::Error::custom)?;
+                Ok(diff)
+            }
+        }
+
+        deserializer.deserialize_any(SparseVecDiffVisitor(PhantomData))
+    }
+}
+
+impl<T: Diffable> Default for SparseVecDiff<T> {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            changes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> PartialEq for SparseVecDiff<T>
+where
+    T: Diffable + PartialEq,
+    <T as Diffable>::Repr: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.changes == other.changes
+    }
+}
+
+impl<T> Eq for SparseVecDiff<T>
+where
+    T: Diffable + Eq,
+    <T as Diffable>::Repr: Eq,
+{
+}
+
+impl<T> Clone for SparseVecDiff<T>
+where
+    T: Diffable + Clone,
+    <T as Diffable>::Repr: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            len: self.len,
+            changes: self.changes.clone(),
+        }
+    }
+}
+
+impl<T: Diffable> Changeable for SparseVecDiff<T> {
+    fn is_changed(&self) -> bool {
+        self.changes.values().any(|v| v.is_changed())
+    }
+}
+
+/// Paths follow the same convention as for [`VecDiff`], the unchanged items in between are not
+/// reported.
+impl<T> Walkable for SparseVecDiff<T>
+where
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        let (mut old, mut new, mut next) = (0, 0, 0);
+
+        for (&position, entry) in &self.changes {
+            // Skipped positions hold unchanged items of both vectors.
+            old += position - next;
+            new += position - next;
+            next = position + 1;
+
+            let index = match entry {
+                CollectionDiffEntry::Added(_) => new,
+                _ => old,
+            };
+            walker.scoped(PathSegment::Index(index), |walker| entry.walk_with(walker));
+
+            match entry {
+                CollectionDiffEntry::Added(_) => new += 1,
+                CollectionDiffEntry::Removed(_) => old += 1,
+                CollectionDiffEntry::Changed(_) | CollectionDiffEntry::Unchanged => {
+                    old += 1;
+                    new += 1;
+                }
+            }
+        }
+    }
+}
+
+fn diff_slices<T>(a: &[T], b: &[T], options: &DiffOptions) -> SparseVecDiff<T>
+where
+    T: Diffable + Clone,
+{
+    let steps = align(a, b, options.vec_algorithm, |a, b| {
+        !a.diff_with(b, options).is_changed()
+    });
+    let len = steps.len();
+    let changes = steps
+        .into_iter()
+        .enumerate()
+        .filter_map(|(position, step)| {
+            let entry = match step {
+                Step::Equal(..) => return None,
+                Step::Pair(i, j) => {
+                    let diff = a[i].diff_with(&b[j], options);
+                    if !diff.is_changed() {
+                        return None;
+                    }
+                    CollectionDiffEntry::Changed(diff)
+                }
+                Step::Removed(i) => CollectionDiffEntry::Removed(a[i].clone()),
+                Step::Added(j) => CollectionDiffEntry::Added(b[j].clone()),
+            };
+            Some((position, entry))
+        })
+        .collect();
+
+    SparseVecDiff { len, changes }
+}
+
+impl<T> SparseDiffable for Vec<T>
+where
+    T: Diffable + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    type SparseRepr = SparseVecDiff<T>;
+
+    fn diff_sparse_with(&self, b: &Self, options: &DiffOptions) -> Self::SparseRepr {
+        diff_slices(self, b, options)
+    }
+}
+
+impl<T> SparseDiffable for [T]
+where
+    T: Diffable + Debug + Clone + PartialEq,
+    for<'de> T: MySerialize<'de>,
+{
+    type SparseRepr = SparseVecDiff<T>;
+
+    fn diff_sparse_with(&self, b: &Self, options: &DiffOptions) -> Self::SparseRepr {
+        diff_slices(self, b, options)
+    }
+}
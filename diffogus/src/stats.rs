@@ -0,0 +1,84 @@
+//! # Change statistics
+//!
+//! [`DiffStats`] counts the leaves of a diff by kind, e.g. to display a summary of a change
+//! without going through it by hand.
+//!
+//! ```rust
+//! use diffogus::diff::Diffable;
+//! use diffogus::stats::DiffStats;
+//!
+//! let a = vec![vec![1, 2], vec![3]];
+//! let b = vec![vec![1, 5, 6]];
+//!
+//! let stats = DiffStats::of(&a.diff(&b));
+//! assert_eq!("1 added, 1 removed, 1 changed", stats.to_string());
+//! assert_eq!(1, stats.unchanged);
+//! assert_eq!(2, stats.max_depth);
+//! ```
+//!
+//! Statistics work on every diff implementing [`Walkable`]: the diffs of built-in types, of JSON
+//! values and of types deriving `Diff` with `#[diff(walk)]`. Without the flag derived diffs
+//! don't implement [`Walkable`], as their field diffs might not either.
+
+use crate::path::Path;
+use crate::visit::{Change, DiffVisitor, Walkable};
+use std::fmt::{self, Display, Formatter};
+
+/// Numbers of leaves of a diff, by kind of change.
+///
+/// Leaves are the parts reported by [`Walkable::walk`]: an added or removed item counts once,
+/// however large it is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiffStats {
+    /// Number of added values.
+    pub added: usize,
+    /// Number of removed values.
+    pub removed: usize,
+    /// Number of replaced values.
+    pub changed: usize,
+    /// Number of unchanged values recorded by the diff.
+    pub unchanged: usize,
+    /// Largest number of path segments leading to a change, `0` if nothing or only the value
+    /// itself changed.
+    pub max_depth: usize,
+}
+
+impl DiffStats {
+    /// Computes the statistics of `diff`. Derived diff types need `#[diff(walk)]` for this.
+    pub fn of<D: Walkable + ?Sized>(diff: &D) -> Self {
+        let mut stats = Self::default();
+        diff.walk(&mut stats);
+        stats
+    }
+
+    /// Total number of added, removed and changed values.
+    pub fn changes(&self) -> usize {
+        self.added + self.removed + self.changed
+    }
+}
+
+impl DiffVisitor for DiffStats {
+    fn visit(&mut self, path: &Path, change: Change<'_>) {
+        match change {
+            Change::Added(_) => self.added += 1,
+            Change::Removed(_) => self.removed += 1,
+            Change::Changed { .. } => self.changed += 1,
+        }
+        self.max_depth = self.max_depth.max(path.0.len());
+    }
+
+    fn visit_unchanged(&mut self, _path: &Path) {
+        self.unchanged += 1;
+    }
+}
+
+/// Summary of the changes, such as `3 added, 1 removed, 12 changed`.
+impl Display for DiffStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.added, self.removed, self.changed
+        )
+    }
+}
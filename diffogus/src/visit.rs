@@ -0,0 +1,246 @@
+//! # Walking diffs
+//!
+//! [`Walkable::walk`] goes through a diff and reports every leaf change to a [`DiffVisitor`],
+//! together with its [`Path`] and the values involved. This makes it possible to log or display
+//! any diff without matching on its representation by hand.
+//!
+//! ```rust
+//! use diffogus::diff::Diffable;
+//! use diffogus::path::Path;
+//! use diffogus::visit::{Change, Walkable};
+//! use std::collections::BTreeMap;
+//!
+//! let a = BTreeMap::from([("a".to_string(), vec![1, 2]), ("b".to_string(), vec![3])]);
+//! let b = BTreeMap::from([("a".to_string(), vec![1, 5, 6])]);
+//!
+//! let mut log = vec![];
+//! a.diff(&b).walk(&mut |path: &Path, change: Change<'_>| {
+//!     log.push(format!("{path} {change:?}"));
+//! });
+//! assert_eq!(
+//!     vec![
+//!         r#".["a"][1] Changed { old: 2, new: 5 }"#,
+//!         r#".["a"][2] Added(6)"#,
+//!         r#".["b"] Removed([3])"#,
+//!     ],
+//!     log
+//! );
+//! ```
+//!
+//! Paths of added `Vec` items point into the new value, all other paths into the old one.
+
+use crate::diff::{
+    BTreeMapDiff, CollectionDiffEntry, Diffable, HashMapDiff, OptionDiff, PrimitiveDiff, VecDiff,
+};
+use crate::path::{Path, PathSegment};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Kind of a leaf [`Change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// A value was added.
+    Added,
+    /// A value was removed.
+    Removed,
+    /// A value was replaced.
+    Changed,
+}
+
+/// Single leaf change reported by [`Walkable::walk`].
+#[derive(Debug, Clone, Copy)]
+pub enum Change<'a> {
+    /// A value was added.
+    Added(&'a dyn Debug),
+    /// A value was removed.
+    Removed(&'a dyn Debug),
+    /// A value was replaced.
+    Changed {
+        /// The old value.
+        old: &'a dyn Debug,
+        /// The new value.
+        new: &'a dyn Debug,
+    },
+}
+
+impl Change<'_> {
+    /// Returns the kind of this change.
+    pub fn kind(&self) -> ChangeKind {
+        match self {
+            Self::Added(_) => ChangeKind::Added,
+            Self::Removed(_) => ChangeKind::Removed,
+            Self::Changed { .. } => ChangeKind::Changed,
+        }
+    }
+}
+
+/// Trait for receivers of the changes reported by [`Walkable::walk`].
+pub trait DiffVisitor {
+    /// Called for every leaf change with its location.
+    fn visit(&mut self, path: &Path, change: Change<'_>);
+
+    /// Called for every unchanged leaf the diff records, such as an unchanged field or `Vec`
+    /// item. Does nothing by default.
+    fn visit_unchanged(&mut self, path: &Path) {
+        let _ = path;
+    }
+}
+
+impl<F> DiffVisitor for F
+where
+    F: FnMut(&Path, Change<'_>),
+{
+    fn visit(&mut self, path: &Path, change: Change<'_>) {
+        self(path, change)
+    }
+}
+
+/// Keeps track of the current path while walking a diff.
+pub struct Walker<'v> {
+    path: Path,
+    visitor: &'v mut dyn DiffVisitor,
+}
+
+impl<'v> Walker<'v> {
+    /// Creates a walker reporting to `visitor`, starting at the empty path.
+    pub fn new(visitor: &'v mut dyn DiffVisitor) -> Self {
+        Self {
+            path: Path::default(),
+            visitor,
+        }
+    }
+
+    /// Runs `f` with `segment` appended to the current path.
+    pub fn scoped<R>(&mut self, segment: PathSegment, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.path.0.push(segment);
+        let out = f(self);
+        self.path.0.pop();
+        out
+    }
+
+    /// Reports `change` at the current path.
+    pub fn report(&mut self, change: Change<'_>) {
+        self.visitor.visit(&self.path, change);
+    }
+
+    /// Reports that the leaf at the current path is unchanged.
+    pub fn report_unchanged(&mut self) {
+        self.visitor.visit_unchanged(&self.path);
+    }
+}
+
+/// Trait for diff representations whose changes can be walked.
+pub trait Walkable {
+    /// Reports every leaf change and unchanged leaf to `walker`, relative to its current path.
+    fn walk_with(&self, walker: &mut Walker<'_>);
+
+    /// Reports every leaf change and unchanged leaf to `visitor`.
+    fn walk(&self, visitor: &mut dyn DiffVisitor) {
+        self.walk_with(&mut Walker::new(visitor));
+    }
+}
+
+impl<T> Walkable for PrimitiveDiff<T>
+where
+    T: Diffable + Debug,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        match self {
+            Self::Changed { old, new } => walker.report(Change::Changed { old, new }),
+            Self::Unchanged => walker.report_unchanged(),
+        }
+    }
+}
+
+impl<T> Walkable for CollectionDiffEntry<T>
+where
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        match self {
+            Self::Added(value) => walker.report(Change::Added(value)),
+            Self::Removed(value) => walker.report(Change::Removed(value)),
+            Self::Changed(diff) => diff.walk_with(walker),
+            Self::Unchanged => walker.report_unchanged(),
+        }
+    }
+}
+
+impl<T> Walkable for OptionDiff<T>
+where
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        match self {
+            Self::Added(value) => walker.report(Change::Added(value)),
+            Self::Removed(value) => walker.report(Change::Removed(value)),
+            Self::Changed(diff) => diff.walk_with(walker),
+            Self::Unchanged => walker.report_unchanged(),
+        }
+    }
+}
+
+impl<T> Walkable for VecDiff<T>
+where
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        let (mut old, mut new) = (0, 0);
+
+        for entry in &self.0 {
+            let index = match entry {
+                CollectionDiffEntry::Added(_) => new,
+                _ => old,
+            };
+            walker.scoped(PathSegment::Index(index), |walker| entry.walk_with(walker));
+
+            match entry {
+                CollectionDiffEntry::Added(_) => new += 1,
+                CollectionDiffEntry::Removed(_) => old += 1,
+                CollectionDiffEntry::Changed(_) | CollectionDiffEntry::Unchanged => {
+                    old += 1;
+                    new += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Walks the entries of a map diff, under their keys.
+pub(crate) fn walk_map<'a, K, T>(
+    entries: impl IntoIterator<Item = (&'a K, &'a CollectionDiffEntry<T>)>,
+    walker: &mut Walker<'_>,
+) where
+    K: Debug + ?Sized + 'a,
+    T: Diffable + Debug + 'a,
+    <T as Diffable>::Repr: Walkable,
+{
+    for (k, entry) in entries {
+        walker.scoped(PathSegment::key(k), |walker| entry.walk_with(walker));
+    }
+}
+
+impl<K, T> Walkable for HashMapDiff<K, T>
+where
+    K: Hash + Eq + Debug,
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        walk_map(&self.0, walker);
+    }
+}
+
+impl<K, T> Walkable for BTreeMapDiff<K, T>
+where
+    K: Hash + Eq + Ord + Debug,
+    T: Diffable + Debug,
+    <T as Diffable>::Repr: Walkable,
+{
+    fn walk_with(&self, walker: &mut Walker<'_>) {
+        walk_map(&self.0, walker);
+    }
+}
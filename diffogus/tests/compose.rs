@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::compose::Composable;
+    use diffogus::diff::{CollectionDiffEntry, Diffable, OptionDiff, PrimitiveDiff};
+    use diffogus::patch::{PatchError, Patchable};
+    use std::collections::{BTreeMap, HashMap};
+    use std::fmt::Debug;
+
+    fn squash<T>(a: T, b: T, c: T) -> T::Repr
+    where
+        T: Patchable + Clone + PartialEq + Debug,
+        T::Repr: Composable,
+    {
+        let diff = Composable::compose(a.diff(&b), b.diff(&c)).unwrap();
+        let mut value = a.clone();
+        value.apply(&diff).unwrap();
+        assert_eq!(c, value);
+        diff
+    }
+
+    #[test]
+    fn test_compose_primitive() {
+        assert_eq!(PrimitiveDiff::Changed { old: 1, new: 3 }, squash(1u8, 2, 3));
+        assert_eq!(PrimitiveDiff::Unchanged, squash(1u8, 2, 1));
+        assert_eq!(
+            PrimitiveDiff::Changed {
+                old: "a".to_string(),
+                new: "b".to_string(),
+            },
+            squash("a".to_string(), "a".to_string(), "b".to_string())
+        );
+
+        let first = PrimitiveDiff::Changed { old: 1, new: 2 };
+        let then = PrimitiveDiff::Changed { old: 5, new: 3 };
+        assert_eq!(Err(PatchError::Mismatch), first.compose(then));
+    }
+
+    #[test]
+    fn test_compose_option() {
+        assert_eq!(OptionDiff::Unchanged, squash(None, Some(1), None));
+        assert_eq!(OptionDiff::Added(3), squash(None, Some(1), Some(3)));
+        assert_eq!(OptionDiff::Removed(1), squash(Some(1), Some(2), None));
+        assert_eq!(
+            OptionDiff::Changed(PrimitiveDiff::Changed { old: 1, new: 3 }),
+            squash(Some(1), None, Some(3))
+        );
+        assert_eq!(OptionDiff::Unchanged, squash(Some(1), None, Some(1)));
+
+        let first = Some(1).diff(&Some(2));
+        assert_eq!(Err(PatchError::Exists), first.compose(OptionDiff::Added(3)));
+        let first = Some(1).diff(&None);
+        assert_eq!(
+            Err(PatchError::Missing),
+            first.compose(OptionDiff::Removed(1))
+        );
+    }
+
+    #[test]
+    fn test_compose_vec() {
+        assert_eq!(
+            vec![
+                CollectionDiffEntry::Unchanged,
+                CollectionDiffEntry::Changed(PrimitiveDiff::Changed { old: 2, new: 4 }),
+            ],
+            squash(vec![1, 2], vec![1, 3], vec![1, 4]).0
+        );
+        squash(vec![1, 2, 3], vec![1], vec![1, 5, 6, 7]);
+        squash(vec![1], vec![1, 2, 3], vec![4]);
+        squash(vec![], vec![1, 2], vec![]);
+        squash(
+            vec![vec![1], vec![2]],
+            vec![vec![1, 1]],
+            vec![vec![], vec![3]],
+        );
+
+        let first = vec![1].diff(&vec![1, 2]);
+        let then = vec![1].diff(&vec![3]);
+        assert_eq!(Err(PatchError::Mismatch), first.compose(then));
+    }
+
+    #[test]
+    fn test_compose_map() {
+        let a = HashMap::from([(1, "a".to_string()), (2, "b".to_string())]);
+        let b = HashMap::from([(2, "c".to_string()), (3, "d".to_string())]);
+        let c = HashMap::from([(1, "a".to_string()), (2, "e".to_string())]);
+        let diff = squash(a.clone(), b, c.clone());
+        assert_eq!(a.diff(&c), diff);
+
+        let a = BTreeMap::from([(1, Some(1))]);
+        let b = BTreeMap::from([(1, None), (2, Some(2))]);
+        let c = BTreeMap::from([(1, Some(3))]);
+        let diff = squash(a.clone(), b.clone(), c.clone());
+        assert_eq!(a.diff(&c), diff);
+
+        let first = a.diff(&b);
+        let then = BTreeMap::new().diff(&BTreeMap::from([(2, Some(5))]));
+        assert_eq!(Err(PatchError::Exists), first.compose(then));
+    }
+}
@@ -1,8 +1,23 @@
 #[cfg(test)]
 mod test {
-    use diffogus::diff::{Diffable, PrimitiveDiff};
+    use diffogus::compose::Composable;
+    use diffogus::diff::{
+        Atomic, Changeable, CollectionDiffEntry, DiffOptions, Diffable, PrimitiveDiff, Tolerance,
+        VecAlgorithm,
+    };
+    use diffogus::invert::Invertible;
+    use diffogus::merge::merge3;
+    use diffogus::patch::{PatchError, Patchable};
+    use diffogus::rebase::{rebase, RejectReason};
+    use diffogus::similarity::{distance, similarity};
+    use diffogus::stats::DiffStats;
+    use diffogus::visit::{Change, ChangeKind, Walkable};
     use diffogus::Diff;
+    use serde::de::DeserializeOwned;
     use serde::{Deserialize, Serialize};
+    use std::borrow::Cow;
+    use std::num::{NonZeroU64, Wrapping};
+    use std::sync::Arc;
 
     #[test]
     fn test_derive() {
@@ -135,4 +150,1441 @@ mod test {
         let diff_str = serde_json::to_string(&diff).unwrap();
         assert_eq!(expected.to_string(), diff_str);
     }
+
+    #[derive(Debug, Clone, PartialEq, Diff, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle { radius: f32, label: String },
+        Rect(u32, u32),
+    }
+
+    #[test]
+    fn test_derive_enum() {
+        let diff = Shape::Empty.diff(&Shape::Empty);
+        assert!(matches!(diff, ShapeDIff::Unchanged));
+
+        let a = Shape::Circle {
+            radius: 1.0,
+            label: "a".into(),
+        };
+        let b = Shape::Circle {
+            radius: 2.0,
+            label: "a".into(),
+        };
+        let diff = a.diff(&b);
+        assert!(diff.is_changed());
+        if let ShapeDIff::CircleChanged { radius, label } = diff {
+            assert_eq!(PrimitiveDiff::Changed { old: 1.0, new: 2.0 }, radius);
+            assert_eq!(PrimitiveDiff::Unchanged, label);
+        } else {
+            unreachable!("Diff is not `CircleChanged`")
+        }
+
+        let diff = Shape::Rect(1, 2).diff(&Shape::Rect(1, 3));
+        if let ShapeDIff::RectChanged(w, h) = diff {
+            assert_eq!(PrimitiveDiff::Unchanged, w);
+            assert_eq!(PrimitiveDiff::Changed { old: 2, new: 3 }, h);
+        } else {
+            unreachable!("Diff is not `RectChanged`")
+        }
+
+        let diff = Shape::Rect(1, 2).diff(&Shape::Rect(1, 2));
+        assert!(!diff.is_changed());
+
+        let diff = Shape::Empty.diff(&Shape::Rect(1, 2));
+        if let ShapeDIff::VariantChanged { old, new } = diff {
+            assert_eq!(Shape::Empty, old);
+            assert_eq!(Shape::Rect(1, 2), new);
+        } else {
+            unreachable!("Diff is not `VariantChanged`")
+        }
+    }
+
+    #[test]
+    fn test_derive_enum_serde() {
+        let diff = Shape::Empty.diff(&Shape::Empty);
+        let expected = r#"{"type":"unchanged"}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let a = Shape::Circle {
+            radius: 1.0,
+            label: "a".into(),
+        };
+        let b = Shape::Circle {
+            radius: 2.0,
+            label: "a".into(),
+        };
+        let diff = a.diff(&b);
+        let expected = r#"{"type":"circle_changed","value":{"radius":{"type":"changed","value":{"old":1.0,"new":2.0}}}}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let diff = Shape::Rect(1, 2).diff(&Shape::Rect(1, 3));
+        let expected = r#"{"type":"rect_changed","value":[{"type":"unchanged"},{"type":"changed","value":{"old":2,"new":3}}]}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let diff = Shape::Empty.diff(&Shape::Rect(1, 2));
+        let expected = r#"{"type":"variant_changed","value":{"old":"Empty","new":{"Rect":[1,2]}}}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+    }
+
+    #[test]
+    fn test_derive_tuple_structs() {
+        #[derive(Debug, Diff)]
+        struct UserId(u64);
+
+        #[derive(Debug, Diff)]
+        struct Point(f32, f32);
+
+        #[derive(Debug, Diff)]
+        struct Marker;
+
+        let diff = UserId(1).diff(&UserId(2));
+        assert!(diff.is_changed());
+        assert_eq!(PrimitiveDiff::Changed { old: 1, new: 2 }, diff.0);
+        assert!(!UserId(1).diff(&UserId(1)).is_changed());
+
+        let diff = Point(1.0, 2.0).diff(&Point(1.0, 3.0));
+        assert!(diff.is_changed());
+        assert_eq!(PrimitiveDiff::Unchanged, diff.0);
+        assert_eq!(PrimitiveDiff::Changed { old: 2.0, new: 3.0 }, diff.1);
+
+        assert!(!Marker.diff(&Marker).is_changed());
+    }
+
+    #[test]
+    fn test_derive_tuple_structs_serde() {
+        #[derive(Debug, Diff)]
+        struct UserId(u64);
+
+        #[derive(Debug, Diff)]
+        struct Point(f32, f32);
+
+        #[derive(Debug, Diff)]
+        struct Marker;
+
+        let diff = UserId(1).diff(&UserId(2));
+        let expected = r#"{"type":"changed","value":{"old":1,"new":2}}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let diff = Point(1.0, 2.0).diff(&Point(1.0, 3.0));
+        let expected = r#"[{"type":"unchanged"},{"type":"changed","value":{"old":2.0,"new":3.0}}]"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let diff = Marker.diff(&Marker);
+        assert_eq!("null", serde_json::to_string(&diff).unwrap());
+    }
+
+    #[test]
+    fn test_derive_generics() {
+        #[derive(Debug, Diff)]
+        struct Page<T> {
+            items: Vec<T>,
+            cursor: Option<T>,
+        }
+
+        #[derive(Debug, Diff)]
+        struct Wrapper<T>(T)
+        where
+            T: Clone;
+
+        #[derive(Debug, Clone, Diff, Serialize, Deserialize)]
+        enum Either<L, R> {
+            Left(L),
+            Right(R),
+        }
+
+        let a = Page {
+            items: vec![1, 2],
+            cursor: None,
+        };
+        let b = Page {
+            items: vec![1, 3],
+            cursor: Some(2),
+        };
+        let diff = a.diff(&b);
+        assert!(diff.is_changed());
+        let expected = r#"{"items":[{"type":"unchanged"},{"type":"changed","value":{"type":"changed","value":{"old":2,"new":3}}}],"cursor":{"type":"added","value":2}}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let diff = Wrapper(String::from("a")).diff(&Wrapper(String::from("b")));
+        assert!(diff.is_changed());
+
+        let diff = Either::<u8, String>::Left(1).diff(&Either::Left(2));
+        assert!(matches!(diff, EitherDIff::LeftChanged(_)));
+        let diff = Either::<u8, String>::Left(1).diff(&Either::Right("a".into()));
+        assert!(matches!(diff, EitherDIff::VariantChanged { .. }));
+    }
+
+    #[test]
+    fn test_derive_generics_custom_bound() {
+        #[derive(Debug, Diff)]
+        #[diff(
+            bound = "T: Copy + Serialize + DeserializeOwned + Diffable<Repr = PrimitiveDiff<T>>"
+        )]
+        struct Pair<T> {
+            left: T,
+            right: T,
+        }
+
+        let diff = Pair { left: 1, right: 2 }.diff(&Pair { left: 1, right: 3 });
+        assert_eq!(PrimitiveDiff::Unchanged, diff.left);
+        assert_eq!(PrimitiveDiff::Changed { old: 2, new: 3 }, diff.right);
+    }
+
+    #[test]
+    fn test_derive_skip() {
+        use std::time::Instant;
+
+        #[derive(Debug, Diff)]
+        struct Entry {
+            name: String,
+            #[diff(skip)]
+            fetched_at: Instant,
+        }
+
+        #[derive(Debug, Diff)]
+        struct Tagged(#[diff(skip)] Instant, u8);
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        struct Cache;
+
+        #[derive(Debug, Clone, Diff, Serialize, Deserialize)]
+        enum Event {
+            Tick {
+                count: u32,
+                #[diff(skip)]
+                cache: Cache,
+            },
+        }
+
+        let a = Entry {
+            name: "a".into(),
+            fetched_at: Instant::now(),
+        };
+        let b = Entry {
+            name: "a".into(),
+            fetched_at: Instant::now(),
+        };
+        assert!(a.fetched_at <= b.fetched_at);
+        let diff = a.diff(&b);
+        assert!(!diff.is_changed());
+        assert_eq!("{}", serde_json::to_string(&diff).unwrap());
+
+        let a = Tagged(Instant::now(), 1);
+        let b = Tagged(Instant::now(), 2);
+        assert!(a.0 <= b.0);
+        let diff = a.diff(&b);
+        assert_eq!(PrimitiveDiff::Changed { old: 1, new: 2 }, diff.0);
+
+        let a = Event::Tick {
+            count: 1,
+            cache: Cache,
+        };
+        let b = Event::Tick {
+            count: 1,
+            cache: Cache,
+        };
+        assert!(!a.diff(&b).is_changed());
+    }
+
+    mod set_diff {
+        use diffogus::diff::PrimitiveDiff;
+        use std::collections::BTreeSet;
+
+        pub type Repr = PrimitiveDiff<Vec<String>>;
+
+        pub fn diff(a: &[String], b: &[String]) -> Repr {
+            let a: BTreeSet<_> = a.iter().collect();
+            let b: BTreeSet<_> = b.iter().collect();
+            if a == b {
+                PrimitiveDiff::Unchanged
+            } else {
+                PrimitiveDiff::Changed {
+                    old: a.into_iter().cloned().collect(),
+                    new: b.into_iter().cloned().collect(),
+                }
+            }
+        }
+    }
+
+    mod url_diff {
+        use diffogus::diff::{Diffable, PrimitiveDiff};
+
+        pub type Repr = PrimitiveDiff<String>;
+
+        pub fn diff(a: &str, b: &str) -> Repr {
+            let normalize = |s: &str| s.trim_end_matches('/').to_lowercase();
+            normalize(a).diff(&normalize(b))
+        }
+    }
+
+    #[test]
+    fn test_derive_with() {
+        #[derive(Debug, Diff)]
+        struct Site {
+            #[diff(with = "url_diff")]
+            url: String,
+            #[diff(with = "set_diff")]
+            tags: Vec<String>,
+        }
+
+        let a = Site {
+            url: "https://example.com/".into(),
+            tags: vec!["a".into(), "b".into()],
+        };
+        let b = Site {
+            url: "https://EXAMPLE.com".into(),
+            tags: vec!["b".into(), "a".into()],
+        };
+        let diff = a.diff(&b);
+        assert!(!diff.is_changed());
+
+        let b = Site {
+            url: "https://example.org".into(),
+            tags: vec!["c".into(), "a".into()],
+        };
+        let diff = a.diff(&b);
+        assert_eq!(
+            PrimitiveDiff::Changed {
+                old: "https://example.com".into(),
+                new: "https://example.org".into(),
+            },
+            diff.url
+        );
+        let expected = r#"{"url":{"type":"changed","value":{"old":"https://example.com","new":"https://example.org"}},"tags":{"type":"changed","value":{"old":["a","b"],"new":["a","c"]}}}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+    }
+
+    #[test]
+    fn test_derive_custom_diffable() {
+        #[derive(Debug, Default, Serialize, Deserialize)]
+        struct Bump(u32);
+
+        impl Changeable for Bump {
+            fn is_changed(&self) -> bool {
+                self.0 != 0
+            }
+        }
+
+        #[derive(Debug)]
+        struct Version(u32);
+
+        impl Diffable for Version {
+            type Repr = Bump;
+
+            fn diff(&self, b: &Self) -> Bump {
+                Bump(b.0.saturating_sub(self.0))
+            }
+        }
+
+        // Field types only need `Diffable` unless the type opts into further impls.
+        #[derive(Debug, Diff)]
+        struct Release {
+            name: String,
+            version: Version,
+        }
+
+        let a = Release {
+            name: "app".into(),
+            version: Version(1),
+        };
+        let b = Release {
+            name: "app".into(),
+            version: Version(3),
+        };
+        let diff = a.diff(&b);
+        assert!(diff.is_changed());
+        assert_eq!(2, diff.version.0);
+        assert_eq!(r#"{"version":2}"#, serde_json::to_string(&diff).unwrap());
+    }
+
+    #[test]
+    fn test_derive_atomic() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        struct Color(u8, u8, u8);
+
+        #[derive(Debug, Clone, PartialEq, Diff, Serialize, Deserialize)]
+        #[diff(atomic)]
+        struct Version {
+            major: u32,
+            minor: u32,
+        }
+
+        #[derive(Debug, Diff)]
+        struct Theme {
+            #[diff(atomic)]
+            color: Color,
+            version: Version,
+        }
+
+        let a = Theme {
+            color: Color(0, 0, 0),
+            version: Version { major: 1, minor: 0 },
+        };
+        let b = Theme {
+            color: Color(0, 0, 0),
+            version: Version { major: 1, minor: 0 },
+        };
+        assert!(!a.diff(&b).is_changed());
+
+        let b = Theme {
+            color: Color(255, 0, 0),
+            version: Version { major: 1, minor: 1 },
+        };
+        let diff = a.diff(&b);
+        assert_eq!(
+            PrimitiveDiff::Changed {
+                old: Atomic(Color(0, 0, 0)),
+                new: Atomic(Color(255, 0, 0)),
+            },
+            diff.color
+        );
+        assert_eq!(
+            PrimitiveDiff::Changed {
+                old: Version { major: 1, minor: 0 },
+                new: Version { major: 1, minor: 1 },
+            },
+            diff.version
+        );
+        let expected = r#"{"color":{"type":"changed","value":{"old":[0,0,0],"new":[255,0,0]}},"version":{"type":"changed","value":{"old":{"major":1,"minor":0},"new":{"major":1,"minor":1}}}}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+    }
+
+    #[test]
+    fn test_derive_tolerance() {
+        #[derive(Debug, Diff)]
+        struct Reading {
+            #[diff(tolerance = 1e-3)]
+            value: f64,
+            #[diff(relative_tolerance = 0.005)]
+            scale: f32,
+            #[diff(tolerance = 0.1)]
+            offset: Option<f32>,
+            #[diff(tolerance = 0.01, relative_tolerance = 0.01)]
+            samples: Vec<f64>,
+        }
+
+        let a = Reading {
+            value: 1.0,
+            scale: 200.0,
+            offset: Some(0.5),
+            samples: vec![1.0, 100.0],
+        };
+        let b = Reading {
+            value: 1.0004,
+            scale: 200.9,
+            offset: Some(0.55),
+            samples: vec![1.005, 100.5],
+        };
+        assert!(!a.diff(&b).is_changed());
+
+        let b = Reading {
+            value: 1.01,
+            scale: 202.0,
+            offset: None,
+            samples: vec![1.0, 103.0],
+        };
+        let diff = a.diff(&b);
+        assert!(diff.value.is_changed());
+        assert!(diff.scale.is_changed());
+        assert!(diff.offset.is_changed());
+        assert!(diff.samples.is_changed());
+        assert!(!diff.samples.0[0].is_changed());
+    }
+
+    #[test]
+    fn test_derive_serde_rename() {
+        #[derive(Debug, Diff, Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct User {
+            user_name: String,
+            #[serde(rename = "mail")]
+            email_address: String,
+            #[diff(rename = "years")]
+            age_in_years: u32,
+        }
+
+        let a = User {
+            user_name: "joe".into(),
+            email_address: "joe@example.com".into(),
+            age_in_years: 30,
+        };
+        let b = User {
+            user_name: "doe".into(),
+            email_address: "doe@example.com".into(),
+            age_in_years: 31,
+        };
+        let diff = a.diff(&b);
+        let expected = r#"{"userName":{"type":"changed","value":{"old":"joe","new":"doe"}},"mail":{"type":"changed","value":{"old":"joe@example.com","new":"doe@example.com"}},"years":{"type":"changed","value":{"old":30,"new":31}}}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let de: UserDIff = serde_json::from_str(expected).unwrap();
+        assert!(de.user_name.is_changed());
+        assert!(de.email_address.is_changed());
+        assert!(de.age_in_years.is_changed());
+
+        #[derive(Debug, Diff, Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        #[diff(rename_all = "SCREAMING_SNAKE_CASE")]
+        struct Overridden {
+            user_name: String,
+        }
+
+        let a = Overridden {
+            user_name: "joe".into(),
+        };
+        let b = Overridden {
+            user_name: "doe".into(),
+        };
+        let expected = r#"{"USER_NAME":{"type":"changed","value":{"old":"joe","new":"doe"}}}"#;
+        assert_eq!(expected, serde_json::to_string(&a.diff(&b)).unwrap());
+    }
+
+    #[test]
+    fn test_derive_serde_rename_enum() {
+        #[derive(Debug, Clone, Diff, Serialize, Deserialize)]
+        #[serde(rename_all_fields = "camelCase")]
+        enum Event {
+            Login {
+                user_name: String,
+            },
+            #[serde(rename_all = "UPPERCASE")]
+            Logout {
+                user_name: String,
+                #[serde(rename = "why")]
+                reason: String,
+            },
+        }
+
+        let a = Event::Login {
+            user_name: "joe".into(),
+        };
+        let b = Event::Login {
+            user_name: "doe".into(),
+        };
+        let expected = r#"{"type":"login_changed","value":{"userName":{"type":"changed","value":{"old":"joe","new":"doe"}}}}"#;
+        assert_eq!(expected, serde_json::to_string(&a.diff(&b)).unwrap());
+
+        let a = Event::Logout {
+            user_name: "joe".into(),
+            reason: "idle".into(),
+        };
+        let b = Event::Logout {
+            user_name: "doe".into(),
+            reason: "manual".into(),
+        };
+        let expected = r#"{"type":"logout_changed","value":{"USER_NAME":{"type":"changed","value":{"old":"joe","new":"doe"}},"why":{"type":"changed","value":{"old":"idle","new":"manual"}}}}"#;
+        assert_eq!(expected, serde_json::to_string(&a.diff(&b)).unwrap());
+    }
+
+    #[test]
+    fn test_derive_name_and_attrs() {
+        #[derive(Debug, Diff)]
+        #[diff(
+            name = "UserChanges",
+            derive(Clone, PartialEq, Eq, Hash),
+            attr(non_exhaustive, allow(dead_code))
+        )]
+        struct User {
+            name: String,
+            tags: Vec<String>,
+            age: Option<u32>,
+        }
+
+        let a = User {
+            name: "joe".into(),
+            tags: vec!["admin".into()],
+            age: None,
+        };
+        let b = User {
+            name: "doe".into(),
+            tags: vec![],
+            age: Some(30),
+        };
+        let diff: UserChanges = a.diff(&b);
+        assert_eq!(diff.clone(), diff);
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(diff.clone());
+        set.insert(a.diff(&a));
+        assert!(set.contains(&diff));
+        assert_eq!(2, set.len());
+
+        #[derive(Debug, Clone, PartialEq, Diff, Serialize, Deserialize)]
+        #[diff(name = "ModeChanges", derive(Clone, PartialEq))]
+        enum Mode {
+            Off,
+            On(u8),
+        }
+
+        let diff: ModeChanges = Mode::On(1).diff(&Mode::On(2));
+        assert_eq!(diff.clone(), diff);
+        assert_eq!(ModeChanges::Unchanged, Mode::Off.diff(&Mode::Off));
+    }
+
+    #[test]
+    fn test_derive_flatten() {
+        #[derive(Debug, Diff, Serialize, Deserialize)]
+        struct Common {
+            name: String,
+            version: u32,
+        }
+
+        #[derive(Debug, Diff, Serialize, Deserialize)]
+        struct Config {
+            #[serde(flatten)]
+            #[diff(flatten)]
+            common: Common,
+            debug: bool,
+        }
+
+        let a = Config {
+            common: Common {
+                name: "app".into(),
+                version: 1,
+            },
+            debug: false,
+        };
+        let b = Config {
+            common: Common {
+                name: "app".into(),
+                version: 2,
+            },
+            debug: true,
+        };
+        let diff = a.diff(&b);
+        assert!(diff.is_changed());
+        assert!(diff.common.version.is_changed());
+
+        let expected = r#"{"version":{"type":"changed","value":{"old":1,"new":2}},"debug":{"type":"changed","value":{"old":false,"new":true}}}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let de: ConfigDIff = serde_json::from_str(expected).unwrap();
+        assert!(!de.common.name.is_changed());
+        assert!(de.common.version.is_changed());
+        assert!(de.debug.is_changed());
+
+        assert_eq!("{}", serde_json::to_string(&a.diff(&a)).unwrap());
+    }
+
+    #[test]
+    fn test_derive_borrowed() {
+        #[derive(Debug, Diff)]
+        struct Row<'a> {
+            name: &'a str,
+            tags: &'a [String],
+            score: &'a u32,
+        }
+
+        #[derive(Debug, Diff)]
+        struct View<'a, T> {
+            value: &'a T,
+            #[diff(skip)]
+            _label: &'a str,
+        }
+
+        let diff = {
+            let (name_a, name_b) = (String::from("joe"), String::from("doe"));
+            let tags_a = vec![String::from("admin")];
+            let tags_b = vec![String::from("admin"), String::from("owner")];
+            let score = 10;
+
+            let a = Row {
+                name: &name_a,
+                tags: &tags_a,
+                score: &score,
+            };
+            let b = Row {
+                name: &name_b,
+                tags: &tags_b,
+                score: &score,
+            };
+            a.diff(&b)
+        };
+
+        assert_eq!(
+            PrimitiveDiff::Changed {
+                old: String::from("joe"),
+                new: String::from("doe"),
+            },
+            diff.name
+        );
+        assert!(diff.tags.is_changed());
+        assert!(!diff.score.is_changed());
+        let expected = r#"{"name":{"type":"changed","value":{"old":"joe","new":"doe"}},"tags":[{"type":"unchanged"},{"type":"added","value":"owner"}]}"#;
+        assert_eq!(expected, serde_json::to_string(&diff).unwrap());
+
+        let diff: ViewDIff<u32> = {
+            let (x, y) = (1, 2);
+            let a = View {
+                value: &x,
+                _label: "a",
+            };
+            let b = View {
+                value: &y,
+                _label: "b",
+            };
+            a.diff(&b)
+        };
+        assert_eq!(PrimitiveDiff::Changed { old: 1, new: 2 }, diff.value);
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        enum Cell {
+            Text(String),
+            Empty,
+        }
+
+        #[derive(Debug, Diff)]
+        struct Slot<'a> {
+            cell: &'a Cell,
+        }
+
+        let diff = {
+            let (x, y) = (Cell::Text("a".into()), Cell::Empty);
+            Slot { cell: &x }.diff(&Slot { cell: &y })
+        };
+        assert!(matches!(
+            diff.cell,
+            CellDIff::VariantChanged {
+                old: Cell::Text(_),
+                new: Cell::Empty,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_derive_patch() {
+        mod set_patch {
+            use super::set_diff;
+            use diffogus::diff::{Changeable, PrimitiveDiff};
+            use diffogus::patch::PatchError;
+
+            pub use set_diff::{diff, Repr};
+
+            pub fn apply(value: &mut Vec<String>, diff: &Repr) -> Result<(), PatchError> {
+                if let PrimitiveDiff::Changed { old, new } = diff {
+                    if set_diff::diff(value, old).is_changed() {
+                        return Err(PatchError::Mismatch);
+                    }
+                    *value = new.clone();
+                }
+                Ok(())
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(patch)]
+        struct Point(i32, i32);
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(atomic, patch)]
+        enum Color {
+            Red,
+            Blue,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(patch)]
+        enum Shape {
+            Circle { radius: f64 },
+            Polygon(Vec<Point>),
+            Empty,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(patch)]
+        struct Canvas<T> {
+            title: T,
+            #[diff(with = "set_patch")]
+            tags: Vec<String>,
+            color: Color,
+            #[diff(atomic)]
+            origin: (u8, u8),
+            shapes: Vec<Shape>,
+            #[diff(skip)]
+            cache: u32,
+        }
+
+        let a = Canvas {
+            title: "sketch".to_string(),
+            tags: vec!["a".into(), "b".into()],
+            color: Color::Red,
+            origin: (0, 0),
+            shapes: vec![
+                Shape::Circle { radius: 1.0 },
+                Shape::Polygon(vec![Point(0, 0), Point(1, 1)]),
+                Shape::Empty,
+            ],
+            cache: 1,
+        };
+        let b = Canvas {
+            title: "drawing".to_string(),
+            tags: vec!["c".into()],
+            color: Color::Blue,
+            origin: (1, 2),
+            shapes: vec![
+                Shape::Circle { radius: 2.0 },
+                Shape::Polygon(vec![Point(0, 1)]),
+                Shape::Circle { radius: 3.0 },
+            ],
+            cache: 2,
+        };
+        let mut patched = a.clone();
+        patched.apply(&a.diff(&b)).unwrap();
+        // Skipped fields keep their value.
+        assert_eq!(Canvas { cache: 1, ..b }, patched);
+
+        let diff = Shape::Circle { radius: 1.0 }.diff(&Shape::Circle { radius: 2.0 });
+        assert_eq!(Err(PatchError::Mismatch), Shape::Empty.apply(&diff));
+        let diff = Shape::Empty.diff(&Shape::Circle { radius: 2.0 });
+        let mut shape = Shape::Circle { radius: 1.0 };
+        assert_eq!(Err(PatchError::Mismatch), shape.apply(&diff));
+        assert_eq!(
+            Err(PatchError::Mismatch),
+            Color::Blue.apply(&Color::Red.diff(&Color::Blue))
+        );
+    }
+
+    #[test]
+    fn test_derive_invert() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(derive(PartialEq), patch, invert)]
+        enum Status {
+            Active { since: u32 },
+            Banned(String),
+        }
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(derive(PartialEq), patch, invert)]
+        struct Account {
+            id: u64,
+            name: Option<String>,
+            roles: Vec<String>,
+            #[diff(atomic)]
+            limits: (u8, u8),
+            status: Status,
+        }
+
+        let a = Account {
+            id: 1,
+            name: Some("joe".into()),
+            roles: vec!["admin".into()],
+            limits: (1, 2),
+            status: Status::Active { since: 1 },
+        };
+        let b = Account {
+            id: 2,
+            name: None,
+            roles: vec!["admin".into(), "user".into()],
+            limits: (3, 4),
+            status: Status::Active { since: 2 },
+        };
+        let diff = a.diff(&b);
+        assert_eq!(b.diff(&a), diff.invert());
+
+        let c = Account {
+            status: Status::Banned("spam".into()),
+            ..b.clone()
+        };
+        let mut value = c.clone();
+        value.apply(&a.diff(&c).invert()).unwrap();
+        assert_eq!(a, value);
+    }
+
+    #[test]
+    fn test_derive_compose() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(derive(PartialEq), patch, invert, compose)]
+        enum Status {
+            Active { since: u32, tags: Vec<String> },
+            Banned(String),
+            Deleted,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(derive(PartialEq), patch, invert, compose)]
+        struct Account {
+            name: Option<String>,
+            #[diff(atomic)]
+            limits: (u8, u8),
+            status: Status,
+        }
+
+        let a = Account {
+            name: Some("joe".into()),
+            limits: (1, 2),
+            status: Status::Active {
+                since: 1,
+                tags: vec!["a".into()],
+            },
+        };
+        let b = Account {
+            name: None,
+            limits: (3, 4),
+            status: Status::Banned("spam".into()),
+        };
+        let c = Account {
+            name: Some("doe".into()),
+            limits: (1, 2),
+            status: Status::Active {
+                since: 1,
+                tags: vec!["b".into()],
+            },
+        };
+        let d = Account {
+            status: Status::Deleted,
+            ..c.clone()
+        };
+
+        let diff = a.diff(&b).compose(b.diff(&c)).unwrap();
+        assert_eq!(a.diff(&c), diff);
+        let diff = diff.compose(c.diff(&d)).unwrap();
+        assert_eq!(a.diff(&d), diff);
+        let diff = b.diff(&c).compose(c.diff(&a)).unwrap();
+        assert_eq!(b.diff(&a), diff);
+
+        let mut value = a.clone();
+        value
+            .apply(&a.diff(&c).compose(c.diff(&b)).unwrap())
+            .unwrap();
+        assert_eq!(b, value);
+
+        let first = c.status.diff(&a.status);
+        let then = Status::Banned("a".into()).diff(&Status::Banned("b".into()));
+        assert_eq!(Err(PatchError::Mismatch), first.compose(then));
+    }
+
+    #[test]
+    fn test_derive_merge() {
+        use std::collections::HashMap;
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(merge)]
+        enum Theme {
+            Light,
+            Custom { background: u32, accent: u32 },
+        }
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(merge)]
+        struct Config {
+            title: String,
+            theme: Theme,
+            plugins: HashMap<String, bool>,
+            #[diff(atomic)]
+            size: (u32, u32),
+            #[diff(tolerance = 0.1)]
+            zoom: f64,
+        }
+
+        let base = Config {
+            title: "Editor".into(),
+            theme: Theme::Custom {
+                background: 0,
+                accent: 1,
+            },
+            plugins: HashMap::from([("spell".into(), true)]),
+            size: (800, 600),
+            zoom: 1.0,
+        };
+        let ours = Config {
+            title: "My editor".into(),
+            theme: Theme::Custom {
+                background: 10,
+                accent: 1,
+            },
+            plugins: HashMap::from([("spell".into(), false)]),
+            size: (1024, 768),
+            zoom: 1.05,
+        };
+        let theirs = Config {
+            title: "Editor".into(),
+            theme: Theme::Custom {
+                background: 0,
+                accent: 2,
+            },
+            plugins: HashMap::from([("spell".into(), true), ("lint".into(), true)]),
+            size: (640, 480),
+            zoom: 2.0,
+        };
+
+        let merge = merge3(&base, &ours, &theirs);
+        let expected = Config {
+            title: "My editor".into(),
+            theme: Theme::Custom {
+                background: 10,
+                accent: 2,
+            },
+            plugins: HashMap::from([("spell".into(), false), ("lint".into(), true)]),
+            size: (1024, 768),
+            zoom: 2.0,
+        };
+        assert_eq!(expected, merge.value);
+        assert_eq!(1, merge.conflicts.len());
+        assert_eq!(".size", merge.conflicts[0].path.to_string());
+
+        let theirs = Config {
+            theme: Theme::Light,
+            ..base.clone()
+        };
+        let merge = merge3(&base, &ours, &theirs);
+        assert_eq!(".theme", merge.conflicts[0].path.to_string());
+        let conflict_theirs = merge.conflicts[0].theirs.as_ref().unwrap();
+        assert_eq!(Some(&Theme::Light), conflict_theirs.as_any().downcast_ref());
+    }
+
+    #[test]
+    fn test_derive_rebase() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(patch, rebase)]
+        enum Shape {
+            Circle { radius: u32 },
+            Square(u32),
+        }
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(patch, rebase)]
+        struct Doc {
+            title: String,
+            shapes: Vec<Shape>,
+            notes: Option<String>,
+        }
+
+        let base = Doc {
+            title: "Doc".into(),
+            shapes: vec![Shape::Circle { radius: 1 }, Shape::Square(2)],
+            notes: Some("draft".into()),
+        };
+        let ours = Doc {
+            title: "My doc".into(),
+            shapes: vec![Shape::Circle { radius: 5 }, Shape::Circle { radius: 3 }],
+            notes: Some("final".into()),
+        };
+        let theirs = Doc {
+            title: "Doc".into(),
+            shapes: vec![Shape::Circle { radius: 1 }, Shape::Square(4)],
+            notes: None,
+        };
+
+        let rebased = rebase(base.diff(&ours), &base.diff(&theirs));
+        let rejected: Vec<_> = rebased
+            .rejected
+            .iter()
+            .map(|r| (r.path.to_string(), r.reason))
+            .collect();
+        assert_eq!(
+            vec![
+                (".shapes[1]".to_string(), RejectReason::Conflict),
+                (".notes".to_string(), RejectReason::Removed),
+            ],
+            rejected
+        );
+
+        let mut value = theirs.clone();
+        value.apply(&rebased.diff).unwrap();
+        let expected = Doc {
+            title: "My doc".into(),
+            shapes: vec![Shape::Circle { radius: 5 }, Shape::Square(4)],
+            notes: None,
+        };
+        assert_eq!(expected, value);
+    }
+
+    #[test]
+    fn test_derive_diff_options() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        enum Shape {
+            Circle { radius: f64 },
+            Label(String),
+        }
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        struct Scene<T> {
+            name: String,
+            shapes: Vec<Shape>,
+            extra: T,
+            updated_at: u64,
+        }
+
+        let a = Scene {
+            name: "Scene".into(),
+            shapes: vec![Shape::Circle { radius: 1.0 }, Shape::Label("Hi".into())],
+            extra: 1.0_f32,
+            updated_at: 1,
+        };
+        let b = Scene {
+            name: "scene".into(),
+            shapes: vec![
+                Shape::Label("new".into()),
+                Shape::Circle { radius: 1.01 },
+                Shape::Label("HI".into()),
+            ],
+            extra: 1.02_f32,
+            updated_at: 2,
+        };
+
+        let diff = a.diff(&b);
+        assert!(diff.name.is_changed());
+        assert!(diff.extra.is_changed());
+        assert!(diff.updated_at.is_changed());
+
+        let options = DiffOptions::new()
+            .ignore_case(true)
+            .tolerance(Tolerance::absolute(0.05))
+            .vec_algorithm(VecAlgorithm::Lcs)
+            .ignore("updated_at");
+        let diff = a.diff_with(&b, &options);
+        assert!(!diff.name.is_changed());
+        assert!(!diff.extra.is_changed());
+        assert!(!diff.updated_at.is_changed());
+        // Matching shapes line up despite the insertion in front of them.
+        assert!(matches!(
+            &diff.shapes.0[..],
+            [
+                CollectionDiffEntry::Added(Shape::Label(_)),
+                CollectionDiffEntry::Unchanged,
+                CollectionDiffEntry::Unchanged,
+            ]
+        ));
+
+        #[derive(Debug, Diff, Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct User {
+            #[serde(rename = "id")]
+            user_id: u32,
+            display_name: String,
+        }
+
+        let a = User {
+            user_id: 1,
+            display_name: "Joe".into(),
+        };
+        let b = User {
+            user_id: 2,
+            display_name: "Doe".into(),
+        };
+        let diff = a.diff_with(&b, &DiffOptions::new().ignore("id").ignore("displayName"));
+        assert!(!diff.is_changed());
+        let diff = a.diff_with(&b, &DiffOptions::new().ignore("user_id"));
+        assert!(diff.user_id.is_changed());
+    }
+
+    #[test]
+    fn test_derive_walk() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(walk)]
+        enum Shape<T> {
+            Circle { radius: T },
+            Point,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(walk)]
+        struct Pair(u8, #[diff(atomic)] Vec<u8>);
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(walk)]
+        struct Scene {
+            r#type: String,
+            shapes: Vec<Shape<u32>>,
+            pair: Pair,
+            #[diff(skip)]
+            cache: u8,
+        }
+
+        let a = Scene {
+            r#type: "2d".into(),
+            shapes: vec![Shape::Circle { radius: 1 }, Shape::Point],
+            pair: Pair(1, vec![1]),
+            cache: 0,
+        };
+        let b = Scene {
+            r#type: "3d".into(),
+            shapes: vec![Shape::Circle { radius: 2 }, Shape::Circle { radius: 3 }],
+            pair: Pair(1, vec![2]),
+            cache: 1,
+        };
+
+        let mut changes = vec![];
+        a.diff(&b)
+            .walk(&mut |path: &diffogus::path::Path, change: Change<'_>| {
+                changes.push((path.to_string(), change.kind(), format!("{change:?}")))
+            });
+        assert_eq!(
+            vec![
+                (
+                    ".type".to_string(),
+                    ChangeKind::Changed,
+                    r#"Changed { old: "2d", new: "3d" }"#.to_string()
+                ),
+                (
+                    ".shapes[0].radius".to_string(),
+                    ChangeKind::Changed,
+                    "Changed { old: 1, new: 2 }".to_string()
+                ),
+                (
+                    ".shapes[1]".to_string(),
+                    ChangeKind::Changed,
+                    "Changed { old: Point, new: Circle { radius: 3 } }".to_string()
+                ),
+                (
+                    ".pair.1".to_string(),
+                    ChangeKind::Changed,
+                    "Changed { old: Atomic([1]), new: Atomic([2]) }".to_string()
+                ),
+            ],
+            changes
+        );
+    }
+
+    #[test]
+    fn test_derive_stats() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(walk)]
+        enum Status {
+            Active { since: u32 },
+            Inactive,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(walk)]
+        struct User {
+            name: String,
+            roles: Vec<String>,
+            status: Status,
+            manager: Option<String>,
+        }
+
+        let a = User {
+            name: "Joe".into(),
+            roles: vec!["admin".into(), "dev".into()],
+            status: Status::Active { since: 1 },
+            manager: None,
+        };
+        let b = User {
+            name: "Joe".into(),
+            roles: vec!["dev".into()],
+            status: Status::Inactive,
+            manager: None,
+        };
+
+        let stats = DiffStats::of(&a.diff(&b));
+        assert_eq!(
+            DiffStats {
+                added: 0,
+                removed: 1,
+                changed: 2,
+                unchanged: 2,
+                max_depth: 2,
+            },
+            stats
+        );
+    }
+
+    #[test]
+    fn test_derive_paths() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[serde(rename_all_fields = "kebab-case")]
+        #[diff(merge, rebase, walk)]
+        enum Owner {
+            Team { team_name: String },
+            Nobody,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(merge, rebase, walk)]
+        struct Meta {
+            created_at: u32,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[serde(rename_all = "camelCase")]
+        #[diff(merge, rebase, walk)]
+        struct Record {
+            #[serde(rename = "id")]
+            record_id: u32,
+            display_name: String,
+            #[diff(rename = "tag")]
+            label: String,
+            #[serde(flatten)]
+            #[diff(flatten)]
+            meta: Meta,
+            owner: Owner,
+        }
+
+        let record =
+            |record_id, display_name: &str, label: &str, created_at, team_name: &str| Record {
+                record_id,
+                display_name: display_name.into(),
+                label: label.into(),
+                meta: Meta { created_at },
+                owner: Owner::Team {
+                    team_name: team_name.into(),
+                },
+            };
+        let base = record(1, "Joe", "a", 1, "core");
+        let ours = record(2, "Joe D", "b", 2, "infra");
+        let theirs = record(3, "Joe", "c", 3, "web");
+
+        let diff = base.diff(&ours);
+        let mut paths = vec![];
+        diff.walk(&mut |path: &diffogus::path::Path, _: Change<'_>| paths.push(path.to_string()));
+        assert_eq!(
+            vec![
+                ".id",
+                ".displayName",
+                ".tag",
+                ".created_at",
+                ".owner.team-name"
+            ],
+            paths
+        );
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(
+            vec!["created_at", "displayName", "id", "owner", "tag"],
+            json.as_object().unwrap().keys().collect::<Vec<_>>()
+        );
+
+        let conflicts = [".id", ".tag", ".created_at", ".owner.team-name"];
+        let merge = merge3(&base, &ours, &theirs);
+        let merge_paths: Vec<_> = merge.conflicts.iter().map(|c| c.path.to_string()).collect();
+        assert_eq!(conflicts.to_vec(), merge_paths);
+
+        let rebased = rebase(base.diff(&ours), &base.diff(&theirs));
+        let rebase_paths: Vec<_> = rebased
+            .rejected
+            .iter()
+            .map(|r| r.path.to_string())
+            .collect();
+        assert_eq!(conflicts.to_vec(), rebase_paths);
+    }
+
+    #[test]
+    fn test_derive_similarity() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(similarity)]
+        enum Shape<T> {
+            Circle { radius: T },
+            Rect(T, T),
+            Point,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
+        #[diff(atomic, similarity)]
+        struct Color(u8, u8, u8);
+
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(similarity)]
+        struct Config<'a> {
+            #[diff(weight = 3)]
+            name: &'a str,
+            #[diff(weight = 0.5)]
+            shape: Shape<u32>,
+            #[diff(atomic)]
+            tags: Vec<String>,
+            color: Color,
+            #[diff(skip)]
+            cache: u8,
+        }
+
+        assert_eq!(1.0, similarity(&Shape::Point::<u8>, &Shape::Point));
+        assert_eq!(0.0, similarity(&Shape::Point, &Shape::Circle { radius: 1 }));
+        assert_eq!(0.5, similarity(&Shape::Rect(1, 2), &Shape::Rect(1, 3)));
+        assert_eq!(
+            1.0,
+            distance(&Shape::Rect(1, 2), &Shape::Circle { radius: 1 })
+        );
+        assert_eq!(2.0, distance(&Shape::Rect(1, 2), &Shape::Rect(3, 4)));
+        assert_eq!(0.0, similarity(&Color(1, 2, 3), &Color(1, 2, 4)));
+
+        let reference = Config {
+            name: "main",
+            shape: Shape::Rect(1, 2),
+            tags: vec!["a".into()],
+            color: Color(0, 0, 0),
+            cache: 0,
+        };
+        let candidate = Config {
+            name: "mail",
+            shape: Shape::Rect(1, 3),
+            tags: vec!["b".into()],
+            color: Color(0, 0, 0),
+            cache: 1,
+        };
+        // (3 * 0.75 + 0.5 * 0.5 + 0 + 1) / 5.5
+        assert_eq!(3.5 / 5.5, similarity(&reference, &candidate));
+        assert_eq!(3.0 + 0.5 + 1.0, distance(&reference, &candidate));
+        assert_eq!(
+            1.0,
+            similarity(
+                &reference,
+                &Config {
+                    cache: 2,
+                    ..reference.clone()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_derive_std_primitives() {
+        #[derive(Debug, Clone, PartialEq, Diff)]
+        #[diff(patch, merge, walk, similarity)]
+        struct Entry {
+            index: usize,
+            offset: isize,
+            marker: char,
+            id: NonZeroU64,
+            counter: Wrapping<u32>,
+            name: Box<str>,
+            owner: Arc<str>,
+            label: Cow<'static, str>,
+            unit: (),
+        }
+
+        let base = Entry {
+            index: 0,
+            offset: -1,
+            marker: 'a',
+            id: NonZeroU64::new(1).unwrap(),
+            counter: Wrapping(0),
+            name: "entry".into(),
+            owner: "me".into(),
+            label: Cow::Borrowed("label"),
+            unit: (),
+        };
+        let ours = Entry {
+            index: 1,
+            label: Cow::Borrowed("new label"),
+            ..base.clone()
+        };
+        let theirs = Entry {
+            marker: 'b',
+            counter: Wrapping(u32::MAX),
+            owner: "them".into(),
+            ..base.clone()
+        };
+
+        let diff = base.diff(&ours);
+        assert_eq!(2, DiffStats::of(&diff).changes());
+        let mut patched = base.clone();
+        patched.apply(&diff).unwrap();
+        assert_eq!(ours, patched);
+
+        let merged = merge3(&base, &ours, &theirs);
+        assert!(merged.is_clean());
+        assert_eq!(
+            Entry {
+                index: 1,
+                label: Cow::Borrowed("new label"),
+                ..theirs.clone()
+            },
+            merged.value
+        );
+        // 4 equal fields, `owner` and `label` by edit distance, out of 9
+        assert_eq!(
+            (4.0 + 1.0 / 4.0 + 5.0 / 9.0) / 9.0,
+            similarity(&ours, &theirs)
+        );
+    }
 }
@@ -1,7 +1,15 @@
 #[cfg(test)]
 mod tests {
-    use diffogus::diff::{Changeable, CollectionDiffEntry, Diffable, OptionDiff, PrimitiveDiff};
+    use diffogus::diff::{
+        ApproxDiffable, Atomic, Changeable, CollectionDiffEntry, DiffOptions, Diffable, OptionDiff,
+        PrimitiveDiff, Tolerance, VecAlgorithm, VecDiff,
+    };
+    use diffogus::patch::Patchable;
+    use std::borrow::Cow;
     use std::collections::HashMap;
+    use std::hash::{BuildHasherDefault, DefaultHasher};
+    use std::num::{NonZeroU32, Wrapping};
+    use std::sync::Arc;
 
     #[test]
     fn test_primitive_diff() {
@@ -118,4 +126,217 @@ mod tests {
         let diff = a.diff(&a);
         assert!(!diff.is_changed());
     }
+
+    #[test]
+    fn test_custom_hasher_diff() {
+        type Map = HashMap<u32, i32, BuildHasherDefault<DefaultHasher>>;
+        let a: Map = [(1, 1), (2, 2)].into_iter().collect();
+        let b: Map = [(1, 1), (2, 3), (3, 4)].into_iter().collect();
+
+        let diff = a.diff(&b);
+        assert!(diff.is_changed());
+        assert_eq!(Some(&CollectionDiffEntry::Added(4)), diff.0.get(&3));
+
+        let mut patched = a.clone();
+        patched.apply(&diff).unwrap();
+        assert_eq!(b, patched);
+    }
+
+    #[test]
+    fn test_std_primitive_diff() {
+        assert_eq!(PrimitiveDiff::Changed { old: 1, new: 2 }, 1_usize.diff(&2));
+        assert!(!(-1_isize).diff(&-1).is_changed());
+        assert_eq!(
+            PrimitiveDiff::Changed { old: 'a', new: 'b' },
+            'a'.diff(&'b')
+        );
+        assert!(!().diff(&()).is_changed());
+
+        let (a, b) = (NonZeroU32::new(1).unwrap(), NonZeroU32::new(2).unwrap());
+        let mut patched = a;
+        patched.apply(&a.diff(&b)).unwrap();
+        assert_eq!(b, patched);
+
+        let (a, b) = (Wrapping(250_u8), Wrapping(250_u8) + Wrapping(10));
+        assert_eq!(PrimitiveDiff::Changed { old: 250, new: 4 }, a.diff(&b));
+        let mut patched = a;
+        patched.apply(&a.diff(&b)).unwrap();
+        assert_eq!(b, patched);
+    }
+
+    #[test]
+    fn test_str_types_diff() {
+        let (a, b): (Box<str>, Box<str>) = ("joe".into(), "doe".into());
+        let diff = a.diff(&b);
+        assert_eq!(
+            PrimitiveDiff::Changed {
+                old: String::from("joe"),
+                new: String::from("doe"),
+            },
+            diff
+        );
+        let mut patched = a.clone();
+        patched.apply(&diff).unwrap();
+        assert_eq!(b, patched);
+
+        let (a, b): (Arc<str>, Arc<str>) = ("Joe".into(), "jOE".into());
+        assert!(a.diff(&b).is_changed());
+        assert!(!a
+            .diff_with(&b, &DiffOptions::new().ignore_case(true))
+            .is_changed());
+
+        let source = String::from("joe");
+        let a = Cow::Borrowed(source.as_str());
+        let b: Cow<'_, str> = Cow::Owned(String::from("doe"));
+        let mut patched = a.clone();
+        patched.apply(&a.diff(&b)).unwrap();
+        assert_eq!(b, patched);
+        assert!(patched.apply(&a.diff(&b)).is_err());
+    }
+
+    #[test]
+    fn test_atomic_diff() {
+        let a = Atomic((1, String::from("a")));
+        let b = Atomic((1, String::from("b")));
+        let diff = a.diff(&b);
+        assert!(diff.is_changed());
+        if let PrimitiveDiff::Changed { old, new } = diff {
+            assert_eq!(old.into_inner(), (1, String::from("a")));
+            assert_eq!(new.into_inner(), (1, String::from("b")));
+        }
+
+        let diff = Atomic::diff_values(&vec![1, 2], &vec![1, 2]);
+        assert!(!diff.is_changed());
+    }
+
+    #[test]
+    fn test_approx_diff() {
+        let tolerance = Tolerance::absolute(1e-3);
+        assert!(!1.0f64.diff_approx(&1.0005, &tolerance).is_changed());
+        assert!(1.0f64.diff_approx(&1.002, &tolerance).is_changed());
+
+        let tolerance = Tolerance::relative(0.01);
+        assert!(!1000.0f32.diff_approx(&1005.0, &tolerance).is_changed());
+        assert!(1.0f32.diff_approx(&1.05, &tolerance).is_changed());
+
+        let tolerance = Tolerance::absolute(0.1);
+        let diff = Some(1.0f64).diff_approx(&Some(1.05), &tolerance);
+        assert_eq!(OptionDiff::Unchanged, diff);
+        let diff = Some(1.0f64).diff_approx(&None, &tolerance);
+        assert_eq!(OptionDiff::Removed(1.0), diff);
+
+        let diff = vec![1.0f64, 2.0].diff_approx(&vec![1.05, 3.0], &tolerance);
+        assert_eq!(CollectionDiffEntry::Unchanged, diff.0[0]);
+        assert_eq!(
+            CollectionDiffEntry::Changed(PrimitiveDiff::Changed { old: 2.0, new: 3.0 }),
+            diff.0[1]
+        );
+    }
+
+    #[test]
+    fn test_borrowed_diff() {
+        let (a, b) = (String::from("joe"), String::from("doe"));
+        let diff = <&str as Diffable>::diff(&a.as_str(), &b.as_str());
+        assert_eq!(
+            PrimitiveDiff::Changed {
+                old: String::from("joe"),
+                new: String::from("doe"),
+            },
+            diff
+        );
+        assert!(!"joe".diff("joe").is_changed());
+
+        let (a, b) = (vec![1, 2], vec![1, 3, 4]);
+        let diff = <&[i32] as Diffable>::diff(&a.as_slice(), &b.as_slice());
+        assert_eq!(CollectionDiffEntry::Unchanged, diff.0[0]);
+        assert_eq!(
+            CollectionDiffEntry::Changed(PrimitiveDiff::Changed { old: 2, new: 3 }),
+            diff.0[1]
+        );
+        assert_eq!(CollectionDiffEntry::Added(4), diff.0[2]);
+
+        let (a, b) = (1.0f64, 1.05f64);
+        assert!(<&f64 as Diffable>::diff(&&a, &&b).is_changed());
+        let diff = <&f64 as ApproxDiffable>::diff_approx(&&a, &&b, &Tolerance::absolute(0.1));
+        assert!(!diff.is_changed());
+    }
+
+    #[test]
+    fn test_diff_options() {
+        let options = DiffOptions::new()
+            .tolerance(Tolerance::absolute(0.1))
+            .ignore_case(true);
+
+        assert!(1.0_f64.diff(&1.05).is_changed());
+        assert!(!1.0_f64.diff_with(&1.05, &options).is_changed());
+        assert!(1.0_f64.diff_with(&1.5, &options).is_changed());
+        assert!(!"Hello".diff_with("hELLO", &options).is_changed());
+        assert!("Hello".diff_with("Hallo", &options).is_changed());
+
+        // Options are forwarded into containers.
+        let a = HashMap::from([("a".to_string(), Some(vec![1.0_f32, 2.0]))]);
+        let b = HashMap::from([("a".to_string(), Some(vec![1.01_f32, 2.0]))]);
+        assert!(a.diff(&b).is_changed());
+        assert!(!a.diff_with(&b, &options).is_changed());
+    }
+
+    #[test]
+    fn test_vec_lcs_diff() {
+        let options = DiffOptions::new().vec_algorithm(VecAlgorithm::Lcs);
+        let a = vec![1, 2, 3, 4];
+        let b = vec![0, 1, 3, 5, 4, 6];
+
+        let diff = a.diff_with(&b, &options);
+        assert_eq!(
+            VecDiff(vec![
+                CollectionDiffEntry::Added(0),
+                CollectionDiffEntry::Unchanged,
+                CollectionDiffEntry::Removed(2),
+                CollectionDiffEntry::Unchanged,
+                CollectionDiffEntry::Added(5),
+                CollectionDiffEntry::Unchanged,
+                CollectionDiffEntry::Added(6),
+            ]),
+            diff
+        );
+        let mut patched = a.clone();
+        patched.apply(&diff).unwrap();
+        assert_eq!(b, patched);
+
+        // Items between matches are paired up as changes.
+        let a = vec!["a".to_string(), "b".into(), "c".into(), "d".into()];
+        let b = vec![
+            "a".to_string(),
+            "x".into(),
+            "y".into(),
+            "z".into(),
+            "d".into(),
+        ];
+        let diff = a.diff_with(&b, &options);
+        assert_eq!(
+            VecDiff(vec![
+                CollectionDiffEntry::Unchanged,
+                CollectionDiffEntry::Changed(PrimitiveDiff::Changed {
+                    old: "b".into(),
+                    new: "x".into(),
+                }),
+                CollectionDiffEntry::Changed(PrimitiveDiff::Changed {
+                    old: "c".into(),
+                    new: "y".into(),
+                }),
+                CollectionDiffEntry::Added("z".into()),
+                CollectionDiffEntry::Unchanged,
+            ]),
+            diff
+        );
+        let mut patched = a.clone();
+        patched.apply(&diff).unwrap();
+        assert_eq!(b, patched);
+
+        assert!(!a.diff_with(&a, &options).is_changed());
+        assert_eq!(
+            VecDiff(vec![CollectionDiffEntry::Removed(1)]),
+            vec![1].diff_with(&vec![], &options)
+        );
+    }
 }
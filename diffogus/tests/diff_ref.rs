@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::diff::{Atomic, Changeable, DiffOptions, Diffable, Tolerance, VecAlgorithm};
+    use diffogus::diff_ref::{
+        CollectionDiffEntryRef, DiffableRef, OptionDiffRef, PrimitiveDiffRef, ToOwnedDiff,
+    };
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_primitive_diff_ref() {
+        let (a, b) = ("old".to_string(), "new".to_string());
+        match a.diff_ref(&b) {
+            PrimitiveDiffRef::Changed { old, new } => {
+                assert!(std::ptr::eq(old, &a));
+                assert!(std::ptr::eq(new, &b));
+            }
+            PrimitiveDiffRef::Unchanged => panic!("expected a change"),
+        }
+        assert!(!1_u8.diff_ref(&1).is_changed());
+        assert_eq!(1.5_f32.diff(&2.5), 1.5_f32.diff_ref(&2.5).to_owned());
+        assert_eq!("a".diff("b"), "a".diff_ref("b").to_owned());
+        assert_eq!(
+            Atomic(vec![1]).diff(&Atomic(vec![2])),
+            Atomic(vec![1]).diff_ref(&Atomic(vec![2])).to_owned()
+        );
+    }
+
+    #[test]
+    fn test_diff_ref_with_options() {
+        let options = DiffOptions::new()
+            .ignore_case(true)
+            .tolerance(Tolerance::absolute(0.1));
+        assert!(!"Hello".diff_ref_with("hello", &options).is_changed());
+        assert!(!1.0_f64.diff_ref_with(&1.05, &options).is_changed());
+
+        let options = DiffOptions::new().vec_algorithm(VecAlgorithm::Lcs);
+        let a = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let b = vec!["x".to_string(), "a".to_string(), "c".to_string()];
+        let diff = a.diff_ref_with(&b, &options);
+        assert!(matches!(diff.0[0], CollectionDiffEntryRef::Added(v) if v == "x"));
+        assert_eq!(a.diff_with(&b, &options), diff.to_owned());
+    }
+
+    #[test]
+    fn test_collection_diff_ref() {
+        let a = vec![Some(1), None, Some(3)];
+        let b = vec![Some(1), Some(2)];
+        let diff = a.diff_ref(&b);
+        assert!(matches!(
+            diff.0[1],
+            CollectionDiffEntryRef::Changed(OptionDiffRef::Added(2))
+        ));
+        assert!(matches!(
+            diff.0[2],
+            CollectionDiffEntryRef::Removed(Some(3))
+        ));
+        assert_eq!(a.diff(&b), diff.to_owned());
+        assert_eq!(a[..].diff(&b[..]), a[..].diff_ref(&b[..]).to_owned());
+
+        let a = HashMap::from([(1, vec![1, 2]), (2, vec![3])]);
+        let b = HashMap::from([(1, vec![1]), (3, vec![])]);
+        let diff = a.diff_ref(&b);
+        assert!(matches!(diff.0[&2], CollectionDiffEntryRef::Removed(v) if v == &[3]));
+        assert_eq!(a.diff(&b), diff.to_owned());
+
+        let a: BTreeMap<_, _> = a.into_iter().collect();
+        let b: BTreeMap<_, _> = b.into_iter().collect();
+        assert_eq!(a.diff(&b), a.diff_ref(&b).to_owned());
+        assert!(!a.diff_ref(&a).is_changed());
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::compose::Composable;
+    use diffogus::diff::{Changeable, CollectionDiffEntry, DiffOptions, Diffable, PrimitiveDiff};
+    use diffogus::indexmap::{IndexSetDiff, KeyOrder};
+    use diffogus::invert::Invertible;
+    use diffogus::merge::merge3;
+    use diffogus::patch::Patchable;
+    use diffogus::rebase::{rebase, RejectReason};
+    use diffogus::similarity::{distance, similarity};
+    use diffogus::stats::DiffStats;
+    use indexmap::{IndexMap, IndexSet};
+
+    fn reorder() -> DiffOptions {
+        DiffOptions::new().detect_reorder(true)
+    }
+
+    #[test]
+    fn test_index_map_diff() {
+        let a = IndexMap::from([('a', 1), ('b', 2), ('c', 3)]);
+        let b = IndexMap::from([('b', 5), ('a', 1), ('d', 4)]);
+
+        let diff = a.diff(&b);
+        assert_eq!(None, diff.order);
+        assert_eq!(
+            vec![
+                (&'a', &CollectionDiffEntry::Unchanged),
+                (
+                    &'b',
+                    &CollectionDiffEntry::Changed(PrimitiveDiff::Changed { old: 2, new: 5 })
+                ),
+                (&'c', &CollectionDiffEntry::Removed(3)),
+                (&'d', &CollectionDiffEntry::Added(4)),
+            ],
+            diff.entries.iter().collect::<Vec<_>>()
+        );
+
+        let diff = a.diff_with(&b, &reorder());
+        assert_eq!(
+            Some(KeyOrder {
+                old: vec!['a', 'b'],
+                new: vec!['b', 'a']
+            }),
+            diff.order
+        );
+        assert_eq!(4, DiffStats::of(&diff).changes());
+
+        let reordered_only = IndexMap::from([('c', 3), ('b', 2), ('a', 1)]);
+        assert!(!a.diff(&reordered_only).is_changed());
+        assert!(a.diff_with(&reordered_only, &reorder()).is_changed());
+    }
+
+    #[test]
+    fn test_index_map_patch() {
+        let a = IndexMap::from([('a', 1), ('b', 2), ('c', 3), ('d', 4)]);
+        let b = IndexMap::from([('d', 4), ('b', 5), ('a', 1), ('e', 6)]);
+
+        let diff = a.diff_with(&b, &reorder());
+        let mut patched = a.clone();
+        patched.apply(&diff).unwrap();
+        assert!(patched.iter().eq(b.iter()));
+
+        let mut unpatched = patched;
+        unpatched.apply(&diff.clone().invert()).unwrap();
+        assert_eq!(a, unpatched);
+        assert!(unpatched.keys().eq([&'a', &'b', &'d', &'c']));
+
+        let c = IndexMap::from([('a', 1), ('d', 4), ('b', 5)]);
+        let composed = diff.compose(b.diff_with(&c, &reorder())).unwrap();
+        let mut patched = a.clone();
+        patched.apply(&composed).unwrap();
+        assert!(patched.iter().eq(c.iter()));
+    }
+
+    #[test]
+    fn test_index_map_rebase_merge() {
+        let base = IndexMap::from([('a', 1), ('b', 2)]);
+        let ours = IndexMap::from([('b', 2), ('a', 3)]);
+        let theirs = IndexMap::from([('b', 2), ('a', 1), ('c', 4)]);
+
+        let rebased = rebase(
+            base.diff_with(&ours, &reorder()),
+            &base.diff_with(&theirs, &reorder()),
+        );
+        assert!(rebased.is_clean());
+        assert_eq!(None, rebased.diff.order);
+
+        let swapped = IndexMap::from([('a', 1), ('b', 2), ('c', 4)]);
+        let theirs_order = IndexMap::from([('b', 2), ('a', 1)]);
+        let rebased = rebase(
+            theirs_order.diff_with(&base, &reorder()),
+            &theirs_order.diff_with(&swapped, &reorder()),
+        );
+        assert!(rebased.is_clean());
+
+        let merged = merge3(&base, &ours, &theirs);
+        assert!(merged.is_clean());
+        assert_eq!(IndexMap::from([('a', 3), ('b', 2), ('c', 4)]), merged.value);
+
+        let other = IndexMap::from([('a', 1), ('b', 2)]);
+        let rebased = rebase(
+            other.diff_with(&IndexMap::from([('b', 2), ('a', 1)]), &reorder()),
+            &IndexMap::from([('a', 1), ('b', 2), ('x', 0)])
+                .diff_with(&IndexMap::from([('b', 2), ('x', 0), ('a', 1)]), &reorder()),
+        );
+        assert_eq!(RejectReason::Conflict, rebased.rejected[0].reason);
+    }
+
+    #[test]
+    fn test_index_set() {
+        let a = IndexSet::from([1, 2, 3]);
+        let b = IndexSet::from([3, 1, 4]);
+
+        assert_eq!(
+            IndexSetDiff {
+                added: vec![4],
+                removed: vec![2],
+                order: None,
+            },
+            a.diff(&b)
+        );
+        let diff = a.diff_with(&b, &reorder());
+        assert_eq!(
+            Some(KeyOrder {
+                old: vec![1, 3],
+                new: vec![3, 1]
+            }),
+            diff.order
+        );
+
+        let mut patched = a.clone();
+        patched.apply(&diff).unwrap();
+        assert!(patched.iter().eq(b.iter()));
+        patched.apply(&diff.clone().invert()).unwrap();
+        assert!(patched.iter().eq([1, 3, 2].iter()));
+
+        let c = IndexSet::from([3, 2]);
+        let composed = a.diff(&b).compose(b.diff(&c)).unwrap();
+        assert_eq!(vec![1], composed.removed);
+        assert!(composed.added.is_empty());
+
+        let merged = merge3(&a, &IndexSet::from([1, 2, 3, 5]), &b);
+        assert_eq!(IndexSet::from([1, 3, 5, 4]), merged.value);
+
+        assert_eq!(0.5, similarity(&a, &b));
+        assert_eq!(2.0, distance(&a, &b));
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::diff::{CollectionDiffEntry, Diffable, OptionDiff, PrimitiveDiff};
+    use diffogus::invert::Invertible;
+    use diffogus::patch::Patchable;
+    use std::collections::{BTreeMap, HashMap};
+    use std::fmt::Debug;
+
+    fn undo<T>(a: T, b: T)
+    where
+        T: Patchable + Clone + PartialEq + Debug,
+        T::Repr: Invertible,
+    {
+        let diff = a.diff(&b);
+        let mut value = b.clone();
+        value.apply(&diff.invert()).unwrap();
+        assert_eq!(a, value);
+    }
+
+    #[test]
+    fn test_invert() {
+        assert_eq!(
+            PrimitiveDiff::Changed { old: 2, new: 1 },
+            1u8.diff(&2).invert()
+        );
+        assert_eq!(PrimitiveDiff::Unchanged, 1u8.diff(&1).invert());
+        assert_eq!(OptionDiff::Added(1), Some(1).diff(&None).invert());
+        assert_eq!(
+            vec![
+                CollectionDiffEntry::Unchanged,
+                CollectionDiffEntry::Changed(PrimitiveDiff::Changed { old: 5, new: 2 }),
+                CollectionDiffEntry::Added(3),
+            ],
+            vec![1, 2, 3].diff(&vec![1, 5]).invert().0
+        );
+    }
+
+    #[test]
+    fn test_invert_undo() {
+        undo(5u8, 7);
+        undo(String::from("joe"), String::from("doe"));
+        undo(Some(1), None);
+        undo(None, Some(vec![1]));
+        undo(vec![1, 2, 3], vec![1, 5]);
+        undo(vec![1], vec![2, 3, 4]);
+        undo(
+            HashMap::from([(1, "a".to_string()), (2, "b".to_string())]),
+            HashMap::from([(2, "c".to_string()), (3, "d".to_string())]),
+        );
+        undo(
+            BTreeMap::from([(1, vec![1]), (2, vec![2])]),
+            BTreeMap::from([(1, vec![1, 1]), (3, vec![3])]),
+        );
+    }
+}
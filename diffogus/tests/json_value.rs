@@ -1,7 +1,16 @@
 #[cfg(test)]
 mod test {
+    use diffogus::compose::Composable;
     use diffogus::diff::*;
+    use diffogus::diff_ref::{CollectionDiffEntryRef, DiffableRef, ToOwnedDiff};
+    use diffogus::invert::Invertible;
     use diffogus::json_value::*;
+    use diffogus::merge::merge3;
+    use diffogus::patch::{PatchError, Patchable};
+    use diffogus::rebase::{rebase, RejectReason};
+    use diffogus::similarity::{distance, similarity};
+    use diffogus::stats::DiffStats;
+    use diffogus::visit::{Change, ChangeKind, Walkable};
     use serde_json::{json, Number};
 
     #[test]
@@ -83,6 +92,13 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_value_diff_eq() {
+        assert_eq!(ValueDiff::Unchanged, json!(1).diff(&json!(1)));
+        assert_eq!(json!([1]).diff(&json!([2])), json!([1]).diff(&json!([2])));
+        assert_ne!(ValueDiff::Unchanged, json!(1).diff(&json!(2)));
+    }
+
     #[test]
     fn test_value_diff_serde() {
         let a = json!(null);
@@ -134,4 +150,280 @@ mod test {
         let expected = r#"{"type":"object_changed","value":{"nested":{"type":"changed","value":{"type":"object_changed","value":{"deep":{"type":"changed","value":{"type":"variant_changed","value":{"old":true,"new":"very"}}}}}}}}"#;
         assert_eq!(expected, serde_json::to_string(&diff).unwrap());
     }
+
+    #[test]
+    fn test_value_patch() {
+        let a = json!({
+            "name": "ball",
+            "size": 10,
+            "tags": ["red", "round"],
+            "nested": { "deep": true, "gone": null }
+        });
+        let b = json!({
+            "name": "ball",
+            "size": 11.5,
+            "tags": ["blue"],
+            "nested": { "deep": "very", "new": [1] }
+        });
+        let mut patched = a.clone();
+        patched.apply(&a.diff(&b)).unwrap();
+        assert_eq!(b, patched);
+
+        let mut patched = json!({ "size": 12 });
+        let diff = json!({ "size": 10 }).diff(&json!({ "size": 11 }));
+        assert_eq!(Err(PatchError::Mismatch), patched.apply(&diff));
+        let mut patched = json!({});
+        assert_eq!(Err(PatchError::Missing), patched.apply(&diff));
+    }
+
+    #[test]
+    fn test_value_invert() {
+        let a = json!({
+            "name": "ball",
+            "size": 10,
+            "tags": ["red", "round"],
+            "nested": { "deep": true, "gone": null }
+        });
+        let b = json!({
+            "name": "bat",
+            "size": 11.5,
+            "tags": ["blue"],
+            "nested": { "deep": "very", "new": [1] }
+        });
+        let diff = a.diff(&b);
+        assert_eq!(b.diff(&a), diff.clone().invert());
+
+        let mut value = b.clone();
+        value.apply(&diff.invert()).unwrap();
+        assert_eq!(a, value);
+    }
+
+    #[test]
+    fn test_value_compose() {
+        let a = json!({
+            "name": "ball",
+            "size": 10,
+            "tags": ["red", "round"],
+            "nested": { "deep": true }
+        });
+        let b = json!({
+            "name": "bat",
+            "size": [1, 2],
+            "tags": ["blue"],
+            "nested": { "deep": "very", "new": [1] }
+        });
+        let c = json!({
+            "name": "ball",
+            "size": [1, 3],
+            "tags": ["blue", "green"],
+            "nested": null
+        });
+        let diff = a.diff(&b).compose(b.diff(&c)).unwrap();
+        assert_eq!(a.diff(&c), diff);
+
+        let mut value = a.clone();
+        value.apply(&diff).unwrap();
+        assert_eq!(c, value);
+
+        let diff = json!([1])
+            .diff(&json!([2]))
+            .compose(json!({}).diff(&json!({ "a": 1 })));
+        assert_eq!(Err(PatchError::Mismatch), diff);
+        let diff = json!(1).diff(&json!(2)).compose(json!(2).diff(&json!(1)));
+        assert!(matches!(diff, Ok(ValueDiff::Unchanged)));
+    }
+
+    #[test]
+    fn test_value_merge() {
+        let base = json!({
+            "name": "ball",
+            "size": 10,
+            "tags": ["red", "round"],
+            "nested": { "deep": true, "keep": 1 }
+        });
+        let ours = json!({
+            "name": "bat",
+            "size": 10,
+            "tags": ["red", "square"],
+            "nested": { "deep": false, "keep": 1 }
+        });
+        let theirs = json!({
+            "name": "ball",
+            "size": 11,
+            "tags": ["blue", "round"],
+            "nested": { "deep": "very" },
+            "new": null
+        });
+        let merge = merge3(&base, &ours, &theirs);
+        let expected = json!({
+            "name": "bat",
+            "size": 11,
+            "tags": ["blue", "square"],
+            "nested": { "deep": false },
+            "new": null
+        });
+        assert_eq!(expected, merge.value);
+        assert_eq!(1, merge.conflicts.len());
+
+        let conflict = &merge.conflicts[0];
+        assert_eq!(r#".["nested"]["deep"]"#, conflict.path.to_string());
+        let theirs = conflict.theirs.as_ref().unwrap();
+        assert_eq!(Some(&json!("very")), theirs.as_any().downcast_ref());
+    }
+
+    #[test]
+    fn test_value_rebase() {
+        let base = json!({
+            "name": "ball",
+            "size": 10,
+            "tags": ["red", "round"],
+            "nested": { "deep": true }
+        });
+        let ours = json!({
+            "name": "bat",
+            "size": 12,
+            "tags": ["red", "round", "big"],
+            "nested": { "deep": false }
+        });
+        let theirs = json!({
+            "name": "ball",
+            "size": 11,
+            "tags": ["red"],
+        });
+        let rebased = rebase(base.diff(&ours), &base.diff(&theirs));
+        let rejected: Vec<_> = rebased
+            .rejected
+            .iter()
+            .map(|r| (r.path.to_string(), r.reason))
+            .collect();
+        assert_eq!(
+            vec![
+                (r#".["nested"]"#.to_string(), RejectReason::Removed),
+                (r#".["size"]"#.to_string(), RejectReason::Conflict),
+            ],
+            rejected
+        );
+
+        let mut value = theirs.clone();
+        value.apply(&rebased.diff).unwrap();
+        let expected = json!({
+            "name": "bat",
+            "size": 11,
+            "tags": ["red", "big"],
+        });
+        assert_eq!(expected, value);
+    }
+
+    #[test]
+    fn test_value_diff_options() {
+        let a = json!({ "name": "Ball", "size": 10.0, "updated": 1, "tags": ["a", "b"] });
+        let b = json!({ "name": "ball", "size": 10.01, "updated": 2, "tags": ["x", "a", "b"] });
+
+        let options = DiffOptions::new()
+            .ignore_case(true)
+            .tolerance(Tolerance::absolute(0.1))
+            .ignore("updated")
+            .vec_algorithm(VecAlgorithm::Lcs);
+        let diff = a.diff_with(&b, &options);
+
+        let mut entries = std::collections::BTreeMap::new();
+        entries.insert("name".to_string(), CollectionDiffEntry::Unchanged);
+        entries.insert("size".to_string(), CollectionDiffEntry::Unchanged);
+        entries.insert(
+            "tags".to_string(),
+            CollectionDiffEntry::Changed(ValueDiff::ArrayChanged(VecDiff(vec![
+                CollectionDiffEntry::Added(json!("x")),
+                CollectionDiffEntry::Unchanged,
+                CollectionDiffEntry::Unchanged,
+            ]))),
+        );
+        assert_eq!(ValueDiff::ObjectChanged(ValueMapDiff(entries)), diff);
+
+        let ValueDiff::ObjectChanged(ValueMapDiff(entries)) = a.diff(&b) else {
+            panic!("expected an object diff");
+        };
+        assert_eq!(4, entries.len());
+        assert!(entries.values().all(Changeable::is_changed));
+    }
+
+    #[test]
+    fn test_value_walk() {
+        let a = json!({ "name": "ball", "tags": ["red"], "size": 1, "nested": { "deep": true } });
+        let b = json!({ "name": "bat", "tags": ["red", "big"], "size": "L", "nested": {} });
+
+        let mut changes = vec![];
+        a.diff(&b)
+            .walk(&mut |path: &diffogus::path::Path, change: Change<'_>| {
+                changes.push((path.to_string(), change.kind()))
+            });
+        assert_eq!(
+            vec![
+                (r#".["name"]"#.to_string(), ChangeKind::Changed),
+                (r#".["nested"]["deep"]"#.to_string(), ChangeKind::Removed),
+                (r#".["size"]"#.to_string(), ChangeKind::Changed),
+                (r#".["tags"][1]"#.to_string(), ChangeKind::Added),
+            ],
+            changes
+        );
+    }
+
+    #[test]
+    fn test_value_stats() {
+        let a = json!({ "name": "ball", "tags": ["red"], "size": 1, "nested": { "deep": true } });
+        let b = json!({ "name": "bat", "tags": ["red", "big"], "size": 1, "nested": {} });
+
+        let stats = DiffStats::of(&a.diff(&b));
+        assert_eq!(
+            DiffStats {
+                added: 1,
+                removed: 1,
+                changed: 1,
+                unchanged: 2,
+                max_depth: 2,
+            },
+            stats
+        );
+    }
+
+    #[test]
+    fn test_value_diff_ref() {
+        let a = json!({ "name": "ball", "tags": ["red"], "size": 1, "id": 1, "ok": true });
+        let b = json!({ "name": "bat", "tags": ["red", "big"], "size": 1.5, "id": 2, "ok": null });
+
+        let diff = a.diff_ref(&b);
+        let ValueDiffRef::ObjectChanged(ValueMapDiffRef(entries)) = &diff else {
+            panic!("expected an object diff, got {diff:?}");
+        };
+        assert!(matches!(
+            entries["name"],
+            CollectionDiffEntryRef::Changed(ValueDiffRef::StringChanged {
+                old: "ball",
+                new: "bat"
+            })
+        ));
+        assert_eq!(a.diff(&b), diff.to_owned());
+        assert_eq!(
+            serde_json::to_value(a.diff(&b)).unwrap(),
+            serde_json::to_value(&diff).unwrap()
+        );
+
+        let options = DiffOptions::new().ignore("id").ignore_case(true);
+        let b = json!({ "name": "BALL", "tags": ["red"], "size": 1, "id": 2, "ok": true });
+        assert!(!a.diff_ref_with(&b, &options).is_changed());
+    }
+
+    #[test]
+    fn test_value_similarity() {
+        assert_eq!(1.0, similarity(&json!(null), &json!(null)));
+        assert_eq!(0.0, similarity(&json!(1), &json!("1")));
+        assert_eq!(0.0, similarity(&json!(1), &json!(1.5)));
+        assert_eq!(0.5, similarity(&json!("ab"), &json!("cb")));
+
+        let a = json!({ "name": "ball", "tags": ["red", "big"], "size": 1 });
+        let b = json!({ "name": "bell", "tags": ["red"], "color": "red" });
+        // name: 0.75, tags: 0.5, size and color: missing.
+        assert_eq!(1.25 / 4.0, similarity(&a, &b));
+        assert_eq!(4.0, distance(&a, &b));
+        assert_eq!(1.0, similarity(&a, &a.clone()));
+    }
 }
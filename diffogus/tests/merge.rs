@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::merge::merge3;
+    use diffogus::path::{Path, PathSegment};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_merge_primitive() {
+        let merge = merge3(&1, &2, &1);
+        assert!(merge.is_clean());
+        assert_eq!(2, merge.value);
+
+        let merge = merge3(&1, &1, &3);
+        assert_eq!(3, merge.value);
+
+        let merge = merge3(&1, &2, &2);
+        assert!(merge.is_clean());
+        assert_eq!(2, merge.value);
+
+        let merge = merge3(&1, &2, &3);
+        assert_eq!(2, merge.value);
+        assert_eq!(1, merge.conflicts.len());
+        let conflict = &merge.conflicts[0];
+        assert_eq!(Path::default(), conflict.path);
+        assert_eq!(".", conflict.path.to_string());
+        let ours = conflict.ours.as_ref().unwrap();
+        let theirs = conflict.theirs.as_ref().unwrap();
+        assert_eq!(Some(&2), ours.as_any().downcast_ref::<i32>());
+        assert_eq!(Some(&3), theirs.as_any().downcast_ref::<i32>());
+        assert_eq!("3", format!("{theirs:?}"));
+    }
+
+    #[test]
+    fn test_merge_option_and_vec() {
+        assert_eq!(Some(3), merge3(&Some(1), &Some(1), &Some(3)).value);
+        assert_eq!(None, merge3(&Some(1), &Some(1), &None).value);
+
+        let merge = merge3(&Some(1), &Some(2), &None);
+        assert_eq!(Some(2), merge.value);
+        assert!(merge.conflicts[0].theirs.is_none());
+
+        let merge = merge3(&vec![1, 2, 3], &vec![5, 2, 3], &vec![1, 2, 6]);
+        assert!(merge.is_clean());
+        assert_eq!(vec![5, 2, 6], merge.value);
+
+        let merge = merge3(&vec![1, 2], &vec![1, 3], &vec![1, 4]);
+        assert_eq!(Path(vec![PathSegment::Index(1)]), merge.conflicts[0].path);
+
+        let merge = merge3(&vec![1, 2], &vec![1, 2, 3], &vec![1, 2]);
+        assert_eq!(vec![1, 2, 3], merge.value);
+        let merge = merge3(&vec![1, 2], &vec![1, 2, 3], &vec![0, 2]);
+        assert_eq!(vec![1, 2, 3], merge.value);
+        assert_eq!(".", merge.conflicts[0].path.to_string());
+    }
+
+    #[test]
+    fn test_merge_maps() {
+        let base = HashMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+            ("c".to_string(), 3),
+        ]);
+        let ours = HashMap::from([
+            ("a".to_string(), 10),
+            ("b".to_string(), 2),
+            ("d".to_string(), 4),
+        ]);
+        let theirs = HashMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 20),
+            ("c".to_string(), 30),
+            ("e".to_string(), 5),
+        ]);
+        let merge = merge3(&base, &ours, &theirs);
+        let expected = HashMap::from([
+            ("a".to_string(), 10),
+            ("b".to_string(), 20),
+            ("d".to_string(), 4),
+            ("e".to_string(), 5),
+        ]);
+        assert_eq!(expected, merge.value);
+        assert_eq!(1, merge.conflicts.len());
+        assert_eq!(r#".["c"]"#, merge.conflicts[0].path.to_string());
+        assert!(merge.conflicts[0].ours.is_none());
+
+        for _ in 0..10 {
+            let base: HashMap<_, _> = (0..20).map(|i| (i, 0)).collect();
+            let ours: HashMap<_, _> = (0..20).map(|i| (i, 1)).collect();
+            let theirs: HashMap<_, _> = (0..20).map(|i| (i, 2)).collect();
+            let paths: Vec<_> = merge3(&base, &ours, &theirs)
+                .conflicts
+                .iter()
+                .map(|c| c.path.to_string())
+                .collect();
+            let mut keys: Vec<_> = (0..20).map(|i| i.to_string()).collect();
+            keys.sort();
+            let sorted: Vec<_> = keys.iter().map(|k| format!(".[{k}]")).collect();
+            assert_eq!(sorted, paths);
+        }
+
+        let base = BTreeMap::from([(1, vec![Some(1)])]);
+        let ours = BTreeMap::from([(1, vec![Some(2)])]);
+        let theirs = BTreeMap::from([(1, vec![None])]);
+        let merge = merge3(&base, &ours, &theirs);
+        assert_eq!(".[1][0]", merge.conflicts[0].path.to_string());
+    }
+}
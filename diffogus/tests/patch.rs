@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::diff::{Atomic, CollectionDiffEntry, Diffable, OptionDiff, PrimitiveDiff};
+    use diffogus::patch::{PatchError, Patchable};
+    use std::collections::{BTreeMap, HashMap};
+    use std::fmt::Debug;
+
+    fn round_trip<T: Patchable + Clone + PartialEq + Debug>(a: T, b: T) {
+        let mut patched = a.clone();
+        patched.apply(&a.diff(&b)).unwrap();
+        assert_eq!(b, patched);
+    }
+
+    #[test]
+    fn test_primitive_patch() {
+        round_trip(5i32, 10);
+        round_trip(1.5f64, 1.5);
+        round_trip(true, false);
+        round_trip(String::from("joe"), String::from("doe"));
+        round_trip(Atomic(vec![1]), Atomic(vec![2]));
+
+        let mut value = 7;
+        let diff = PrimitiveDiff::Changed { old: 5, new: 10 };
+        assert_eq!(Err(PatchError::Mismatch), value.apply(&diff));
+        assert_eq!(7, value);
+    }
+
+    #[test]
+    fn test_option_patch() {
+        round_trip(Some(1), Some(2));
+        round_trip(None, Some(2));
+        round_trip(Some(1), None);
+        round_trip(None::<u8>, None);
+
+        assert_eq!(
+            Err(PatchError::Exists),
+            Some(1).apply(&OptionDiff::Added(2))
+        );
+        assert_eq!(
+            Err(PatchError::Missing),
+            None.apply(&OptionDiff::Removed(2))
+        );
+        assert_eq!(
+            Err(PatchError::Mismatch),
+            Some(1).apply(&OptionDiff::Removed(2))
+        );
+    }
+
+    #[test]
+    fn test_vec_patch() {
+        round_trip(vec![1, 2, 3], vec![1, 5]);
+        round_trip(vec![1], vec![1, 2, 3]);
+        round_trip(Vec::<u8>::new(), vec![]);
+        round_trip(vec![vec![1], vec![]], vec![vec![2, 3]]);
+
+        let diff = vec![1, 2].diff(&vec![1, 3]);
+        assert_eq!(Err(PatchError::Missing), vec![1].apply(&diff));
+        assert_eq!(Err(PatchError::Mismatch), vec![1, 2, 3].apply(&diff));
+
+        let diff = vec![1, 2, 3].diff(&vec![1, 5]);
+        let mut value = vec![0, 2, 4];
+        assert_eq!(Err(PatchError::Mismatch), value.apply(&diff));
+        assert_eq!(vec![0, 2, 4], value);
+
+        let diff = vec![vec![1], vec![2]].diff(&vec![vec![3], vec![4]]);
+        let mut value = vec![vec![1], vec![9]];
+        assert_eq!(Err(PatchError::Mismatch), value.apply(&diff));
+        assert_eq!(2, value.len());
+    }
+
+    #[test]
+    fn test_map_patch() {
+        let a = HashMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+            ("c".to_string(), 3),
+        ]);
+        let b = HashMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 5),
+            ("d".to_string(), 4),
+        ]);
+        round_trip(a.clone(), b.clone());
+
+        let diff = a.diff(&b);
+        let mut missing = HashMap::from([("a".to_string(), 1), ("c".to_string(), 3)]);
+        assert_eq!(Err(PatchError::Missing), missing.apply(&diff));
+        let mut exists = a.clone();
+        exists.insert("d".to_string(), 4);
+        assert_eq!(Err(PatchError::Exists), exists.apply(&diff));
+
+        let a = BTreeMap::from([(1, "a".to_string()), (2, "b".to_string())]);
+        let b = BTreeMap::from([(2, "c".to_string()), (3, "d".to_string())]);
+        round_trip(a.clone(), b);
+
+        let mut diff = a.diff(&a);
+        diff.0
+            .insert(1, CollectionDiffEntry::Removed("z".to_string()));
+        assert_eq!(Err(PatchError::Mismatch), a.clone().apply(&diff));
+    }
+}
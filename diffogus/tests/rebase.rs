@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::diff::{CollectionDiffEntry, Diffable, OptionDiff, PrimitiveDiff, VecDiff};
+    use diffogus::patch::Patchable;
+    use diffogus::path::{Path, PathSegment};
+    use diffogus::rebase::{rebase, RejectReason, Rejection};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_rebase_primitive() {
+        let rebased = rebase(1.diff(&2), &1.diff(&1));
+        assert!(rebased.is_clean());
+        assert_eq!(PrimitiveDiff::Changed { old: 1, new: 2 }, rebased.diff);
+
+        let rebased = rebase(1.diff(&2), &1.diff(&2));
+        assert!(rebased.is_clean());
+        assert_eq!(PrimitiveDiff::Unchanged, rebased.diff);
+
+        let rebased = rebase(1.diff(&2), &1.diff(&3));
+        assert_eq!(PrimitiveDiff::Unchanged, rebased.diff);
+        assert_eq!(
+            vec![Rejection {
+                path: Path::default(),
+                reason: RejectReason::Conflict,
+            }],
+            rebased.rejected
+        );
+
+        let rebased = rebase(Some(1).diff(&Some(2)), &Some(1).diff(&None));
+        assert_eq!(OptionDiff::Unchanged, rebased.diff);
+        assert_eq!(RejectReason::Removed, rebased.rejected[0].reason);
+    }
+
+    #[test]
+    fn test_rebase_vec() {
+        let base = vec![1, 2, 3];
+        let ours = base.diff(&vec![5, 2, 3, 4]);
+        let theirs = base.diff(&vec![1, 2]);
+        let rebased = rebase(ours, &theirs);
+        assert!(rebased.is_clean());
+
+        let mut value = vec![1, 2];
+        value.apply(&rebased.diff).unwrap();
+        assert_eq!(vec![5, 2, 4], value);
+
+        // Items inserted by the other diff shift ours.
+        let theirs = VecDiff(vec![
+            CollectionDiffEntry::Added(0),
+            CollectionDiffEntry::Unchanged,
+            CollectionDiffEntry::Removed(2),
+            CollectionDiffEntry::Unchanged,
+        ]);
+        let ours = base.diff(&vec![1, 2, 6]);
+        let rebased = rebase(ours, &theirs);
+        assert!(rebased.is_clean());
+        let mut value = vec![0, 1, 3];
+        value.apply(&rebased.diff).unwrap();
+        assert_eq!(vec![0, 1, 6], value);
+
+        let ours = base.diff(&vec![1, 7, 3]);
+        let rebased = rebase(ours, &theirs);
+        assert_eq!(Path(vec![PathSegment::Index(1)]), rebased.rejected[0].path);
+        assert_eq!(RejectReason::Removed, rebased.rejected[0].reason);
+
+        let rebased = rebase(base.diff(&vec![1]), &vec![1].diff(&vec![2]));
+        assert_eq!(RejectReason::Mismatch, rebased.rejected[0].reason);
+    }
+
+    #[test]
+    fn test_rebase_maps() {
+        let base = HashMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+            ("c".to_string(), 3),
+        ]);
+        let ours = HashMap::from([
+            ("a".to_string(), 10),
+            ("b".to_string(), 20),
+            ("d".to_string(), 4),
+        ]);
+        let theirs = HashMap::from([
+            ("a".to_string(), 1),
+            ("c".to_string(), 3),
+            ("e".to_string(), 5),
+        ]);
+        let rebased = rebase(base.diff(&ours), &base.diff(&theirs));
+        assert_eq!(1, rebased.rejected.len());
+        assert_eq!(r#".["b"]"#, rebased.rejected[0].path.to_string());
+        assert_eq!(RejectReason::Removed, rebased.rejected[0].reason);
+
+        let mut value = theirs.clone();
+        value.apply(&rebased.diff).unwrap();
+        let expected = HashMap::from([
+            ("a".to_string(), 10),
+            ("d".to_string(), 4),
+            ("e".to_string(), 5),
+        ]);
+        assert_eq!(expected, value);
+
+        let base = BTreeMap::from([(1, vec![1, 2])]);
+        let ours = BTreeMap::from([(1, vec![1, 3]), (2, vec![])]);
+        let theirs = BTreeMap::from([(1, vec![0, 2]), (2, vec![1])]);
+        let rebased = rebase(base.diff(&ours), &base.diff(&theirs));
+        assert_eq!(".[2]", rebased.rejected[0].path.to_string());
+        assert_eq!(RejectReason::Conflict, rebased.rejected[0].reason);
+
+        let mut value = theirs.clone();
+        value.apply(&rebased.diff).unwrap();
+        assert_eq!(BTreeMap::from([(1, vec![0, 3]), (2, vec![1])]), value);
+    }
+}
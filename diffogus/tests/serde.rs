@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod test {
     use diffogus::diff::{Changeable, CollectionDiffEntry, Diffable, HashMapDiff};
+    use diffogus::diff_ref::DiffableRef;
+    use diffogus::sparse::{SparseDiffable, SparseHashMapDiff, SparseVecDiff};
     use serde_json::json;
+    use std::collections::HashMap;
 
     #[test]
     fn test_serde_changed() {
@@ -144,4 +147,68 @@ mod test {
         assert!(!diff.get(&1).unwrap().is_changed());
         assert_eq!(*diff.get(&2).unwrap(), CollectionDiffEntry::Removed(3));
     }
+
+    #[test]
+    fn test_diff_ref_serde() {
+        let a = HashMap::from([
+            ("a".to_string(), vec![Some(1), None]),
+            ("b".to_string(), vec![]),
+        ]);
+        let b = HashMap::from([("a".to_string(), vec![Some(2)]), ("c".to_string(), vec![])]);
+        assert_eq!(
+            serde_json::to_value(a.diff(&b)).unwrap(),
+            serde_json::to_value(a.diff_ref(&b)).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_string(&"a".diff("b")).unwrap(),
+            serde_json::to_string(&"a".diff_ref("b")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sparse_serde() {
+        let a = HashMap::from([(0, 0), (1, 1), (2, 2)]);
+        let b = HashMap::from([(0, 0), (1, 5), (3, 3)]);
+        let diff = a.diff_sparse(&b);
+        let expected = json!({
+            "1": { "type": "changed", "value": { "type": "changed", "value": { "old": 1, "new": 5 } } },
+            "2": { "type": "removed", "value": 2 },
+            "3": { "type": "added", "value": 3 }
+        });
+        assert_eq!(expected, serde_json::to_value(&diff).unwrap());
+        let dense = serde_json::to_value(a.diff(&b)).unwrap();
+        assert_eq!(
+            diff,
+            serde_json::from_value::<SparseHashMapDiff<i32, i32>>(dense).unwrap()
+        );
+
+        let a = vec![1, 2, 3];
+        let b = vec![1, 5, 3, 4];
+        let diff = a.diff_sparse(&b);
+        let expected = json!({
+            "len": 4,
+            "changes": {
+                "1": { "type": "changed", "value": { "type": "changed", "value": { "old": 2, "new": 5 } } },
+                "3": { "type": "added", "value": 4 }
+            }
+        });
+        assert_eq!(expected, serde_json::to_value(&diff).unwrap());
+        assert_eq!(
+            diff,
+            serde_json::from_value::<SparseVecDiff<i32>>(expected).unwrap()
+        );
+        let dense = serde_json::to_value(a.diff(&b)).unwrap();
+        assert_eq!(
+            diff,
+            serde_json::from_value::<SparseVecDiff<i32>>(dense).unwrap()
+        );
+
+        let out_of_range = SparseVecDiff::<i32> {
+            len: 1,
+            changes: [(1, CollectionDiffEntry::Added(4))].into(),
+        };
+        assert!(serde_json::to_string(&out_of_range).is_err());
+        let out_of_range = json!({ "len": 1, "changes": { "1": { "type": "added", "value": 4 } } });
+        assert!(serde_json::from_value::<SparseVecDiff<i32>>(out_of_range).is_err());
+    }
 }
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::diff::Atomic;
+    use diffogus::similarity::{distance, similarity, Similar};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_primitive_similarity() {
+        assert_eq!(1.0, similarity(&1, &1));
+        assert_eq!(0.0, similarity(&1, &2));
+        assert_eq!(1.0, distance(&1.0, &2.0));
+        assert_eq!(0.0, distance(&true, &true));
+        assert_eq!(0.0, similarity(&Atomic(vec![1]), &Atomic(vec![2])));
+
+        assert_eq!(1.0, similarity("", ""));
+        assert_eq!(0.0, similarity("abc", ""));
+        assert_eq!(0.75, similarity("über", "uber"));
+        assert_eq!(3.0, distance(&"sitting".to_string(), &"kitten".to_string()));
+        assert_eq!(1.0, distance(&&"ab", &&"b"));
+
+        assert_eq!(1.0, similarity(&None::<u8>, &None));
+        assert_eq!(0.0, similarity(&Some(1), &None));
+        assert_eq!(
+            0.5,
+            similarity(&Some("ab".to_string()), &Some("ac".to_string()))
+        );
+        assert_eq!(1.0, distance(&None, &Some(1)));
+    }
+
+    #[test]
+    fn test_collection_similarity() {
+        assert_eq!(1.0, Vec::<u8>::new().similarity(&vec![]));
+        assert_eq!(0.5, vec![1, 2].similarity(&vec![1, 3]));
+        assert_eq!(0.25, vec![1, 2, 3, 4].similarity(&vec![1]));
+        assert_eq!(3.0, vec![1, 2, 3, 4].distance(&vec![1]));
+        assert_eq!(0.75, vec![0, 1, 2, 3].similarity(&vec![1, 2, 3]));
+        assert_eq!(1.0, vec![0, 1, 2, 3].distance(&vec![1, 2, 3]));
+        assert_eq!(1.0, vec![1, 2, 3].distance(&vec![9, 1, 2, 3]));
+        let a = vec!["abcd".to_string(), "x".to_string()];
+        let b = vec!["abce".to_string(), "x".to_string()];
+        assert_eq!(0.875, a.similarity(&b));
+        assert_eq!(1.0, a.distance(&b));
+
+        let a = HashMap::from([("a", 1), ("b", 2), ("c", 3)].map(|(k, v)| (k.to_string(), v)));
+        let b = HashMap::from([("a", 1), ("b", 5), ("d", 3)].map(|(k, v)| (k.to_string(), v)));
+        assert_eq!(0.25, a.similarity(&b));
+        assert_eq!(3.0, a.distance(&b));
+
+        let a = BTreeMap::from([(1, vec![1, 2])]);
+        let b = BTreeMap::from([(1, vec![1, 2, 3, 4])]);
+        assert_eq!(0.5, a.similarity(&b));
+        assert_eq!(2.0, a.distance(&b));
+    }
+}
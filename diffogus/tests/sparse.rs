@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::diff::{
+        Changeable, CollectionDiffEntry, DiffOptions, Diffable, PrimitiveDiff, VecAlgorithm,
+        VecDiff,
+    };
+    use diffogus::path::Path;
+    use diffogus::sparse::{
+        PositionOutOfRange, SparseBTreeMapDiff, SparseDiffable, SparseHashMapDiff, SparseVecDiff,
+    };
+    use diffogus::visit::{Change, Walkable};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_sparse_map_diff() {
+        let a = HashMap::from([(1, 1), (2, 2), (3, 3)]);
+        let b = HashMap::from([(1, 1), (2, 5), (4, 4)]);
+        let diff = a.diff_sparse(&b);
+
+        assert!(diff.is_changed());
+        assert_eq!(3, diff.len());
+        assert_eq!(None, diff.get(&1));
+        assert!(diff.is_unchanged_at(&1));
+        assert!(diff.is_unchanged_at(&9));
+        assert!(!diff.is_unchanged_at(&2));
+        assert_eq!(Some(&CollectionDiffEntry::Removed(3)), diff.get(&3));
+        assert_eq!(Some(&CollectionDiffEntry::Added(4)), diff.get(&4));
+        assert_eq!(SparseHashMapDiff::from(a.diff(&b)), diff);
+        assert!(a.diff_sparse(&a).is_empty());
+        assert!(!a.diff_sparse(&a).is_changed());
+
+        let a: BTreeMap<_, _> = a.into_iter().collect();
+        let b: BTreeMap<_, _> = b.into_iter().collect();
+        let diff = a.diff_sparse(&b);
+        assert_eq!(
+            vec![&2, &3, &4],
+            diff.iter().map(|(k, _)| k).collect::<Vec<_>>()
+        );
+        assert_eq!(SparseBTreeMapDiff::from(a.diff(&b)), diff);
+    }
+
+    #[test]
+    fn test_sparse_vec_diff() {
+        let a = vec![1, 2, 3, 4];
+        let b = vec![1, 5, 3];
+        let diff = a.diff_sparse(&b);
+
+        assert_eq!(4, diff.len);
+        assert!(diff.is_unchanged_at(0));
+        assert_eq!(
+            Some(&CollectionDiffEntry::Changed(PrimitiveDiff::Changed {
+                old: 2,
+                new: 5
+            })),
+            diff.get(1)
+        );
+        assert_eq!(Some(&CollectionDiffEntry::Removed(4)), diff.get(3));
+        assert_eq!(vec![1, 3], diff.iter().map(|(i, _)| i).collect::<Vec<_>>());
+        assert_eq!(SparseVecDiff::from(a.diff(&b)), diff);
+        assert_eq!(a.diff(&b), VecDiff::try_from(diff).unwrap());
+        assert_eq!(
+            a.diff(&b),
+            VecDiff::try_from(a[..].diff_sparse(&b[..])).unwrap()
+        );
+
+        let options = DiffOptions::new().vec_algorithm(VecAlgorithm::Lcs);
+        let b = vec![0, 1, 2, 3, 4];
+        let diff = a.diff_sparse_with(&b, &options);
+        assert_eq!(1, diff.changes.len());
+        assert_eq!(a.diff_with(&b, &options), VecDiff::try_from(diff).unwrap());
+
+        let diff = SparseVecDiff {
+            len: 2,
+            changes: BTreeMap::from([
+                (0, CollectionDiffEntry::Added(1)),
+                (2, CollectionDiffEntry::Added(3)),
+            ]),
+        };
+        assert_eq!(
+            Err(PositionOutOfRange {
+                position: 2,
+                len: 2
+            }),
+            VecDiff::try_from(diff)
+        );
+    }
+
+    #[test]
+    fn test_sparse_walk() {
+        let a = vec![1, 2, 3, 4, 5];
+        let b = vec![9, 2, 3, 5];
+        let options = DiffOptions::new().vec_algorithm(VecAlgorithm::Lcs);
+
+        let mut log = vec![];
+        let mut visitor = |path: &Path, change: Change<'_>| log.push(format!("{path} {change:?}"));
+        a.diff_sparse_with(&b, &options).walk(&mut visitor);
+
+        let mut expected = vec![];
+        let mut visitor =
+            |path: &Path, change: Change<'_>| expected.push(format!("{path} {change:?}"));
+        a.diff_with(&b, &options).walk(&mut visitor);
+        assert_eq!(expected, log);
+        assert_eq!(
+            vec![".[0] Changed { old: 1, new: 9 }", ".[3] Removed(4)"],
+            log
+        );
+    }
+}
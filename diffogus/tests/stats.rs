@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::diff::Diffable;
+    use diffogus::stats::DiffStats;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_primitive_stats() {
+        assert_eq!(
+            DiffStats {
+                unchanged: 1,
+                ..Default::default()
+            },
+            DiffStats::of(&1.diff(&1))
+        );
+        assert_eq!(
+            DiffStats {
+                changed: 1,
+                ..Default::default()
+            },
+            DiffStats::of(&"a".diff("b"))
+        );
+        assert_eq!(1, DiffStats::of(&None.diff(&Some(1))).added);
+        assert_eq!(0, DiffStats::of(&Vec::<u8>::new().diff(&vec![])).changes());
+    }
+
+    #[test]
+    fn test_nested_stats() {
+        let a = HashMap::from([
+            ("a".to_string(), vec![Some(1), None]),
+            ("b".to_string(), vec![]),
+            ("c".to_string(), vec![Some(3)]),
+        ]);
+        let b = HashMap::from([
+            ("a".to_string(), vec![Some(2), Some(0), Some(1)]),
+            ("c".to_string(), vec![Some(3)]),
+            ("d".to_string(), vec![]),
+        ]);
+
+        let stats = DiffStats::of(&a.diff(&b));
+        assert_eq!(
+            DiffStats {
+                added: 3,
+                removed: 1,
+                changed: 1,
+                unchanged: 1,
+                max_depth: 2,
+            },
+            stats
+        );
+        assert_eq!(5, stats.changes());
+        assert_eq!("3 added, 1 removed, 1 changed", stats.to_string());
+    }
+}
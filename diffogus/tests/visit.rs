@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use diffogus::diff::{DiffOptions, Diffable, VecAlgorithm};
+    use diffogus::path::Path;
+    use diffogus::visit::{Change, ChangeKind, DiffVisitor, Walkable};
+    use std::collections::{BTreeMap, HashMap};
+
+    /// Collects the changes as `(path, kind, rendered change)`.
+    #[derive(Default)]
+    struct Log(Vec<(String, ChangeKind, String)>);
+
+    impl DiffVisitor for Log {
+        fn visit(&mut self, path: &Path, change: Change<'_>) {
+            let rendered = match change {
+                Change::Added(new) => format!("+{new:?}"),
+                Change::Removed(old) => format!("-{old:?}"),
+                Change::Changed { old, new } => format!("{old:?} -> {new:?}"),
+            };
+            self.0.push((path.to_string(), change.kind(), rendered));
+        }
+    }
+
+    fn log(diff: &impl Walkable) -> Vec<(String, ChangeKind, String)> {
+        let mut log = Log::default();
+        diff.walk(&mut log);
+        log.0
+    }
+
+    fn entry(path: &str, kind: ChangeKind, rendered: &str) -> (String, ChangeKind, String) {
+        (path.to_string(), kind, rendered.to_string())
+    }
+
+    #[test]
+    fn test_walk_primitives() {
+        assert!(log(&1.diff(&1)).is_empty());
+        assert_eq!(
+            vec![entry(".", ChangeKind::Changed, "1 -> 2")],
+            log(&1.diff(&2))
+        );
+        assert_eq!(
+            vec![entry(".", ChangeKind::Added, "+\"a\"")],
+            log(&None.diff(&Some("a".to_string())))
+        );
+        assert_eq!(
+            vec![entry(".", ChangeKind::Changed, "true -> false")],
+            log(&Some(true).diff(&Some(false)))
+        );
+    }
+
+    #[test]
+    fn test_walk_vec() {
+        let a = vec![vec![1, 2], vec![3]];
+        let b = vec![vec![1, 4], vec![], vec![5]];
+        assert_eq!(
+            vec![
+                entry(".[0][1]", ChangeKind::Changed, "2 -> 4"),
+                entry(".[1][0]", ChangeKind::Removed, "-3"),
+                entry(".[2]", ChangeKind::Added, "+[5]"),
+            ],
+            log(&a.diff(&b))
+        );
+
+        // Added items point into the new value, the others into the old one.
+        let options = DiffOptions::new().vec_algorithm(VecAlgorithm::Lcs);
+        let diff = vec![1, 2, 3].diff_with(&vec![0, 1, 3], &options);
+        assert_eq!(
+            vec![
+                entry(".[0]", ChangeKind::Added, "+0"),
+                entry(".[1]", ChangeKind::Removed, "-2"),
+            ],
+            log(&diff)
+        );
+    }
+
+    #[test]
+    fn test_walk_maps() {
+        let a = BTreeMap::from([(1, "a".to_string()), (2, "b".to_string())]);
+        let b = BTreeMap::from([(2, "c".to_string()), (3, "d".to_string())]);
+        assert_eq!(
+            vec![
+                entry(".[1]", ChangeKind::Removed, "-\"a\""),
+                entry(".[2]", ChangeKind::Changed, "\"b\" -> \"c\""),
+                entry(".[3]", ChangeKind::Added, "+\"d\""),
+            ],
+            log(&a.diff(&b))
+        );
+
+        let a = HashMap::from([("k".to_string(), Some(1))]);
+        let b = HashMap::from([("k".to_string(), Some(2))]);
+        let mut paths = vec![];
+        a.diff(&b)
+            .walk(&mut |path: &Path, _: Change<'_>| paths.push(path.clone()));
+        assert_eq!(r#".["k"]"#, paths[0].to_string());
+    }
+}
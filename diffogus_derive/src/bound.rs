@@ -0,0 +1,258 @@
+//! Inference of `where` clauses for the generated diff types and impls.
+
+use crate::field::{DiffField, Strategy};
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+#[cfg(feature = "serde")]
+use quote::quote;
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::token::{Comma, Plus};
+use syn::{parse_quote, GenericParam, Generics, Ident, Type, TypeParamBound, WherePredicate};
+
+/// Returns `true` if `ty` mentions any of the `params` anywhere in its tokens.
+fn mentions_params(ty: &Type, params: &[&Ident]) -> bool {
+    fn walk(tokens: TokenStream2, params: &[&Ident]) -> bool {
+        tokens.into_iter().any(|tt| match tt {
+            TokenTree::Ident(ident) => params.iter().any(|p| **p == ident),
+            TokenTree::Group(group) => walk(group.stream(), params),
+            _ => false,
+        })
+    }
+
+    walk(ty.to_token_stream(), params)
+}
+
+/// Returns `true` if `tokens` mention any of the lifetime parameters of `generics`.
+pub(crate) fn mentions_lifetimes(tokens: &impl ToTokens, generics: &Generics) -> bool {
+    fn walk(tokens: TokenStream2, lifetimes: &[&Ident]) -> bool {
+        let mut apostrophe = false;
+        tokens.into_iter().any(|tt| {
+            let found = match &tt {
+                TokenTree::Ident(ident) => apostrophe && lifetimes.contains(&ident),
+                TokenTree::Group(group) => walk(group.stream(), lifetimes),
+                _ => false,
+            };
+            apostrophe = matches!(&tt, TokenTree::Punct(p) if p.as_char() == '\'');
+            found
+        })
+    }
+
+    let lifetimes: Vec<_> = generics.lifetimes().map(|l| &l.lifetime.ident).collect();
+    !lifetimes.is_empty() && walk(tokens.to_token_stream(), &lifetimes)
+}
+
+/// Generics of a generated type that doesn't borrow from the input.
+///
+/// Lifetime parameters are dropped, along with every bound and predicate mentioning them.
+pub(crate) fn without_lifetimes(generics: &Generics) -> Generics {
+    let strip = |bounds: &mut Punctuated<TypeParamBound, Plus>| {
+        *bounds = std::mem::take(bounds)
+            .into_iter()
+            .filter(|b| {
+                !matches!(b, TypeParamBound::Lifetime(_)) && !mentions_lifetimes(b, generics)
+            })
+            .collect();
+    };
+
+    let mut out = generics.clone();
+    out.params = std::mem::take(&mut out.params)
+        .into_iter()
+        .filter(|p| !matches!(p, GenericParam::Lifetime(_)))
+        .collect();
+    for param in out.type_params_mut() {
+        strip(&mut param.bounds);
+    }
+    if let Some(where_clause) = &mut out.where_clause {
+        where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+            .into_iter()
+            .filter_map(|predicate| match predicate {
+                WherePredicate::Type(mut predicate)
+                    if !mentions_lifetimes(&predicate.bounded_ty, generics) =>
+                {
+                    strip(&mut predicate.bounds);
+                    (!predicate.bounds.is_empty()).then_some(WherePredicate::Type(predicate))
+                }
+                _ => None,
+            })
+            .collect();
+    }
+
+    out
+}
+
+/// Fields whose type depends on a type parameter, deduplicated by representation.
+fn generic_fields<'a, 'f>(
+    generics: &Generics,
+    fields: &'a [DiffField<'f>],
+) -> Vec<&'a DiffField<'f>> {
+    let params: Vec<_> = generics.type_params().map(|p| &p.ident).collect();
+    let mut seen = vec![];
+    let mut out = vec![];
+
+    for field in fields {
+        let key = field.repr().to_string();
+        if mentions_params(field.ty(), &params) && !seen.contains(&key) {
+            seen.push(key);
+            out.push(field);
+        }
+    }
+
+    out
+}
+
+/// Adds the bounds required for diffing `fields` to `generics`.
+///
+/// Unless `custom` bounds are given, every type parameter used by a field diffed through its
+/// own impl gets a `Diffable` bound, as does every field type that depends on a type parameter.
+pub(crate) fn with_diffable_bounds(
+    generics: &Generics,
+    fields: &[DiffField],
+    custom: Option<&Punctuated<WherePredicate, Comma>>,
+) -> Generics {
+    let mut generics = generics.clone();
+    let predicates: Vec<WherePredicate> = match custom {
+        Some(custom) => custom.iter().cloned().collect(),
+        None => {
+            let fields = generic_fields(&generics, fields);
+            let mut predicates = vec![];
+
+            for param in generics.type_params() {
+                let ident = &param.ident;
+                let diffed = fields.iter().any(|f| {
+                    matches!(f.strategy, Strategy::Diffable | Strategy::Approx { .. })
+                        && mentions_params(f.ty(), &[ident])
+                });
+                if !diffed {
+                    continue;
+                }
+                predicates.push(parse_quote! { #ident: ::diffogus::diff::Diffable });
+                #[cfg(feature = "serde")]
+                predicates.push(parse_quote! { #ident: for<'__de> ::diffogus::MySerialize<'__de> });
+            }
+
+            for field in fields {
+                let Some(ty) = field.diffable_ty() else {
+                    continue;
+                };
+                predicates.push(match field.strategy {
+                    Strategy::Approx { .. } => {
+                        parse_quote! { #ty: ::diffogus::diff::ApproxDiffable }
+                    }
+                    _ => parse_quote! { #ty: ::diffogus::diff::Diffable },
+                });
+            }
+
+            predicates
+        }
+    };
+
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+/// Adds the bounds required for applying diffs to `fields` to `generics`, which already carries
+/// the bounds of the `Diffable` impl.
+pub(crate) fn with_patch_bounds(generics: &Generics, fields: &[DiffField]) -> Generics {
+    let mut generics = generics.clone();
+    let predicates: Vec<WherePredicate> = generic_fields(&generics, fields)
+        .into_iter()
+        .filter_map(DiffField::diffable_ty)
+        .map(|ty| parse_quote! { #ty: ::diffogus::patch::Patchable })
+        .collect();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+/// Adds the bounds required for merging `fields` to `generics`, which already carries the bounds
+/// of the `Diffable` impl. Fields without their own `Diffable` impl are merged as a whole.
+pub(crate) fn with_merge_bounds(generics: &Generics, fields: &[DiffField]) -> Generics {
+    let mut generics = generics.clone();
+    let predicates: Vec<WherePredicate> = generic_fields(&generics, fields)
+        .into_iter()
+        .map(|field| {
+            let ty = &field.diffed_ty;
+            match field.strategy {
+                Strategy::Diffable => parse_quote! { #ty: ::diffogus::merge::Mergeable },
+                _ => parse_quote! { #ty: ::core::clone::Clone + ::core::fmt::Debug + 'static },
+            }
+        })
+        .collect();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+/// Adds the bounds required for scoring the similarity of `fields` to `generics`, which already
+/// carries the bounds of the `Diffable` impl.
+pub(crate) fn with_similar_bounds(generics: &Generics, fields: &[DiffField]) -> Generics {
+    let mut generics = generics.clone();
+    let predicates: Vec<WherePredicate> = generic_fields(&generics, fields)
+        .into_iter()
+        .filter(|field| matches!(field.strategy, Strategy::Diffable))
+        .map(|field| {
+            let ty = &field.diffed_ty;
+            parse_quote! { #ty: ::diffogus::similarity::Similar }
+        })
+        .collect();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+/// Adds `bound` on the representations of generic fields to `generics`, for impls on a generated
+/// diff type that delegate to its field diffs.
+pub(crate) fn with_repr_bounds(
+    generics: &Generics,
+    fields: &[DiffField],
+    bound: &TokenStream2,
+) -> Generics {
+    let mut generics = generics.clone();
+    let predicates: Vec<WherePredicate> = generic_fields(&generics, fields)
+        .into_iter()
+        .map(|field| {
+            let repr = field.repr();
+            parse_quote! { #repr: #bound }
+        })
+        .collect();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+/// Bounds for the `Default` impl of a generated diff type.
+///
+/// The `Diffable` bounds on field types hide their concrete representation, so the
+/// representations of generic fields have to be required to implement `Default` explicitly.
+pub(crate) fn with_default_bounds(generics: &Generics, fields: &[DiffField]) -> Generics {
+    let mut generics = generics.clone();
+    let predicates = default_predicates(&generics, fields);
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+fn default_predicates(generics: &Generics, fields: &[DiffField]) -> Vec<WherePredicate> {
+    generic_fields(generics, fields)
+        .into_iter()
+        .map(|field| {
+            let repr = field.repr();
+            parse_quote! { #repr: ::core::default::Default }
+        })
+        .collect()
+}
+
+/// Bounds for deserializing a generated diff type, whose fields fall back to `Default`.
+#[cfg(feature = "serde")]
+pub(crate) fn serde_bound(generics: &Generics, fields: &[DiffField]) -> TokenStream2 {
+    let predicates = default_predicates(generics, fields);
+    let bound = quote! { #(#predicates),* }.to_string();
+
+    quote! { #[serde(bound = #bound)] }
+}
+
+/// Bounds placed on the `Diffable` impl so the generated representation satisfies `Diffable::Repr`.
+pub(crate) fn repr_bounds(generics: &mut Generics, repr: &TokenStream2) {
+    if generics.params.is_empty() {
+        return;
+    }
+
+    generics.make_where_clause().predicates.push(parse_quote! {
+        #repr: ::core::fmt::Debug + for<'__de> ::diffogus::MySerialize<'__de>
+    });
+}
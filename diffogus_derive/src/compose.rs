@@ -0,0 +1,113 @@
+//! Generation of `Composable` impls for derived diff types.
+
+use crate::bound;
+use crate::changed_variant_ident;
+use crate::field::DiffField;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Generics, Ident, Variant};
+
+/// `Composable` impl for a generated diff struct, composing every field diff.
+pub(crate) fn struct_impl(
+    struct_name: &Ident,
+    generics: &Generics,
+    fields: &[DiffField],
+) -> TokenStream2 {
+    let generics = bound::with_repr_bounds(
+        generics,
+        fields,
+        &quote! { ::diffogus::compose::Composable },
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let diff_members = fields.iter().map(|f| &f.diff_member);
+    let unused = fields.is_empty().then(|| quote! { let _ = then; });
+
+    quote! {
+        impl #impl_generics ::diffogus::compose::Composable for #struct_name #ty_generics #where_clause {
+            fn compose(
+                self,
+                then: Self,
+            ) -> ::core::result::Result<Self, ::diffogus::patch::PatchError> {
+                #unused
+                ::core::result::Result::Ok(Self {
+                    #(#diff_members: ::diffogus::compose::Composable::compose(
+                        self.#diff_members,
+                        then.#diff_members,
+                    )?),*
+                })
+            }
+        }
+    }
+}
+
+/// `Composable` impl for a generated diff enum.
+///
+/// Changes within the same variant are composed field by field. When either diff changes the
+/// variant, the other one is applied to the value it records in full.
+pub(crate) fn enum_impl(
+    enum_name: &Ident,
+    generics: &Generics,
+    variants: &[(&Variant, Vec<DiffField>)],
+) -> TokenStream2 {
+    let fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let generics = bound::with_patch_bounds(generics, &fields);
+    let generics = bound::with_repr_bounds(
+        &generics,
+        &fields,
+        &quote! { ::diffogus::compose::Composable + ::diffogus::invert::Invertible },
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let changed_arms = variants
+        .iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(variant, fields)| {
+            let changed_ident = changed_variant_ident(variant);
+            let diff_members: Vec<_> = fields.iter().map(|f| &f.diff_member).collect();
+            let a: Vec<_> = fields.iter().map(|f| f.binding("__a")).collect();
+            let b: Vec<_> = fields.iter().map(|f| f.binding("__b")).collect();
+
+            quote! {
+                (
+                    Self::#changed_ident { #(#diff_members: #a),* },
+                    Self::#changed_ident { #(#diff_members: #b),* },
+                ) => Self::#changed_ident {
+                    #(#diff_members: ::diffogus::compose::Composable::compose(#a, #b)?),*
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics ::diffogus::compose::Composable for #enum_name #ty_generics #where_clause {
+            fn compose(
+                self,
+                then: Self,
+            ) -> ::core::result::Result<Self, ::diffogus::patch::PatchError> {
+                #[allow(unreachable_patterns)]
+                let diff = match (self, then) {
+                    (Self::Unchanged, diff) | (diff, Self::Unchanged) => diff,
+                    (Self::VariantChanged { old, new: mut value }, then) => {
+                        ::diffogus::patch::Patchable::apply(&mut value, &then)?;
+                        ::diffogus::diff::Diffable::diff(&old, &value)
+                    }
+                    (first, Self::VariantChanged { old: mut value, new }) => {
+                        let first = ::diffogus::invert::Invertible::invert(first);
+                        ::diffogus::patch::Patchable::apply(&mut value, &first)?;
+                        ::diffogus::diff::Diffable::diff(&value, &new)
+                    }
+                    #(#changed_arms,)*
+                    _ => return ::core::result::Result::Err(::diffogus::patch::PatchError::Mismatch),
+                };
+
+                ::core::result::Result::Ok(if ::diffogus::diff::Changeable::is_changed(&diff) {
+                    diff
+                } else {
+                    Self::Unchanged
+                })
+            }
+        }
+    }
+}
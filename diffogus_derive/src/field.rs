@@ -0,0 +1,377 @@
+//! Fields of the input type and their `#[diff(...)]` attributes.
+
+use crate::parse_attrs;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use structmeta::{Flag, NameValue, StructMeta};
+use syn::ext::IdentExt;
+use syn::{
+    parse_quote, Attribute, Expr, ExprUnary, Field, Fields, Ident, Index, LitStr, Member, Path,
+    Type, UnOp,
+};
+
+#[derive(StructMeta, Default)]
+pub(crate) struct FieldAttrs {
+    skip: bool,
+    atomic: bool,
+    with: Option<NameValue<LitStr>>,
+    tolerance: Option<NameValue<Expr>>,
+    relative_tolerance: Option<NameValue<Expr>>,
+    rename: Option<NameValue<LitStr>>,
+    flatten: Flag,
+    weight: Option<NameValue<Expr>>,
+}
+
+impl FieldAttrs {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        parse_attrs(attrs)
+    }
+}
+
+/// How a field is diffed.
+#[derive(Clone)]
+pub(crate) enum Strategy {
+    /// Through the field type's own `Diffable` impl.
+    Diffable,
+    /// As a whole through `PartialEq`, set by `#[diff(atomic)]`.
+    Atomic,
+    /// Through a module providing `Repr` and `diff`, set by `#[diff(with = "...")]`.
+    With(Path),
+    /// Through `ApproxDiffable` with the given absolute and relative tolerances, set by
+    /// `#[diff(tolerance = ...)]` and `#[diff(relative_tolerance = ...)]`.
+    Approx {
+        absolute: Option<Box<Expr>>,
+        relative: Option<Box<Expr>>,
+    },
+}
+
+/// A field that takes part in the diff.
+#[derive(Clone)]
+pub(crate) struct DiffField<'a> {
+    /// Member used to access the field on the input type.
+    pub(crate) member: Member,
+    /// Member holding the field's diff on the generated type.
+    pub(crate) diff_member: Member,
+    pub(crate) field: &'a Field,
+    /// Field type without its leading shared references, which diff like the value they
+    /// point to. Fields diffed `with` a module keep their type.
+    pub(crate) diffed_ty: Type,
+    /// Number of references peeled off to get `diffed_ty`.
+    pub(crate) derefs: usize,
+    pub(crate) strategy: Strategy,
+    /// Serialized name set by `#[diff(rename = "...")]`.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) rename: Option<LitStr>,
+    /// Whether the field's diff is serialized inline, set by `#[diff(flatten)]`.
+    pub(crate) flatten: bool,
+    /// Weight of the field in similarity scores, set by `#[diff(weight = ...)]`.
+    pub(crate) weight: Option<NameValue<Expr>>,
+    /// Name of the field in paths. With the `serde` feature this is the name the field's diff
+    /// is serialized under.
+    pub(crate) path_name: String,
+}
+
+impl DiffField<'_> {
+    pub(crate) fn ty(&self) -> &Type {
+        &self.field.ty
+    }
+
+    /// Type whose `Diffable` impl diffs the field, if any.
+    pub(crate) fn diffable_ty(&self) -> Option<Type> {
+        let ty = &self.diffed_ty;
+        match &self.strategy {
+            Strategy::Diffable | Strategy::Approx { .. } => Some(ty.clone()),
+            Strategy::Atomic => Some(parse_quote! { ::diffogus::diff::Atomic<#ty> }),
+            Strategy::With(_) => None,
+        }
+    }
+
+    /// Type of the field on the generated diff type.
+    pub(crate) fn repr(&self) -> TokenStream2 {
+        match (&self.strategy, self.diffable_ty()) {
+            (Strategy::With(with), _) => quote! { #with::Repr },
+            (_, ty) => quote! { <#ty as ::diffogus::diff::Diffable>::Repr },
+        }
+    }
+
+    /// Expression computing the diff between the two references `a` and `b` to the field, with
+    /// the `DiffOptions` behind the reference `options`.
+    pub(crate) fn diff(&self, a: &Ident, b: &Ident, options: &TokenStream2) -> TokenStream2 {
+        let derefs = vec![quote! { * }; self.derefs];
+        let (a, b) = (quote! { #(#derefs)* #a }, quote! { #(#derefs)* #b });
+
+        match &self.strategy {
+            Strategy::Diffable => {
+                quote! { ::diffogus::diff::Diffable::diff_with(#a, #b, #options) }
+            }
+            Strategy::Atomic => quote! { ::diffogus::diff::Atomic::diff_values(#a, #b) },
+            Strategy::With(with) => quote! { #with::diff(#a, #b) },
+            Strategy::Approx { absolute, relative } => {
+                let zero: Expr = parse_quote! { 0.0 };
+                let absolute = absolute.as_deref().unwrap_or(&zero);
+                let relative = relative.as_deref().unwrap_or(&zero);
+                quote! {
+                    ::diffogus::diff::ApproxDiffable::diff_approx(#a, #b, &::diffogus::diff::Tolerance {
+                        absolute: (#absolute) as f64,
+                        relative: (#relative) as f64,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Like [`DiffField::diff`], with the options in `options`, but unchanged when the options
+    /// ignore the field under its path name.
+    pub(crate) fn diff_unless_ignored(&self, a: &Ident, b: &Ident) -> TokenStream2 {
+        let name = &self.path_name;
+        let diff = self.diff(a, b, &quote! { options });
+        quote! {
+            if options.is_ignored(#name) {
+                ::core::default::Default::default()
+            } else {
+                #diff
+            }
+        }
+    }
+
+    /// Statement applying the diff `d` to the field behind the mutable reference `v`.
+    pub(crate) fn apply(&self, v: &TokenStream2, d: &TokenStream2) -> TokenStream2 {
+        match &self.strategy {
+            Strategy::Diffable | Strategy::Approx { .. } => {
+                quote! { ::diffogus::patch::Patchable::apply(#v, #d)? }
+            }
+            Strategy::Atomic => quote! { ::diffogus::diff::Atomic::apply_value(#v, #d)? },
+            Strategy::With(with) => quote! { #with::apply(#v, #d)? },
+        }
+    }
+
+    /// Expression merging the field from the references `base` and `theirs` into `ours`.
+    pub(crate) fn merge(
+        &self,
+        ours: &TokenStream2,
+        base: &TokenStream2,
+        theirs: &TokenStream2,
+    ) -> TokenStream2 {
+        if let Strategy::Diffable = self.strategy {
+            return quote! { ::diffogus::merge::Mergeable::merge(#ours, #base, #theirs, merger) };
+        }
+
+        let (a, b) = (
+            Ident::new("__a", Span::call_site()),
+            Ident::new("__b", Span::call_site()),
+        );
+        let diff = self.diff(
+            &a,
+            &b,
+            &quote! { &::diffogus::diff::DiffOptions::default() },
+        );
+        quote! {
+            merger.merge_leaf_by(#ours, #base, #theirs, |#a, #b| {
+                ::diffogus::diff::Changeable::is_changed(&#diff)
+            })
+        }
+    }
+
+    /// Expressions scoring the references `a` and `b` to the field as `(similarity, distance)`.
+    /// Fields without their own `Similar` impl are either equal or completely different.
+    pub(crate) fn similarity(&self, a: &Ident, b: &Ident) -> (TokenStream2, TokenStream2) {
+        if let Strategy::Diffable = self.strategy {
+            let derefs = vec![quote! { * }; self.derefs];
+            let (a, b) = (quote! { #(#derefs)* #a }, quote! { #(#derefs)* #b });
+            return (
+                quote! { ::diffogus::similarity::Similar::similarity(#a, #b) },
+                quote! { ::diffogus::similarity::Similar::distance(#a, #b) },
+            );
+        }
+
+        let diff = self.diff(a, b, &quote! { &::diffogus::diff::DiffOptions::default() });
+        let changed = quote! { ::diffogus::diff::Changeable::is_changed(&#diff) };
+        (
+            quote! { if #changed { 0.0 } else { 1.0 } },
+            quote! { if #changed { 1.0 } else { 0.0 } },
+        )
+    }
+
+    /// Weight of the field in similarity scores, `1.0` unless set.
+    pub(crate) fn weight(&self) -> TokenStream2 {
+        match &self.weight {
+            Some(weight) => {
+                let weight = &weight.value;
+                quote! { ((#weight) as f64) }
+            }
+            None => quote! { 1.0 },
+        }
+    }
+
+    /// Wraps `body` in a scope of `scoper` under the field's path name, rebinding `scoper` in
+    /// the closure. Flattened fields share the path of the value they are flattened into.
+    pub(crate) fn scoped(&self, scoper: &str, body: TokenStream2) -> TokenStream2 {
+        if self.flatten {
+            return body;
+        }
+        let scoper = Ident::new(scoper, Span::call_site());
+        let name = &self.path_name;
+        quote! {
+            #scoper.scoped(::diffogus::path::PathSegment::Field(#name), |#scoper| #body)
+        }
+    }
+
+    /// Name of the field on the input type, its index for tuple fields.
+    pub(crate) fn name(&self) -> String {
+        match &self.member {
+            Member::Named(ident) => ident.unraw().to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        }
+    }
+
+    /// Name of the binding holding this field when destructuring the input.
+    pub(crate) fn binding(&self, prefix: &str) -> Ident {
+        match &self.member {
+            Member::Named(ident) => {
+                Ident::new(&format!("{prefix}_{}", ident.unraw()), ident.span())
+            }
+            Member::Unnamed(index) => {
+                Ident::new(&format!("{prefix}_{}", index.index), Span::call_site())
+            }
+        }
+    }
+}
+
+/// Collects the fields that are not marked with `#[diff(skip)]`.
+pub(crate) fn diff_fields(fields: &Fields) -> syn::Result<Vec<DiffField<'_>>> {
+    let mut out = vec![];
+
+    for (field, member) in fields.iter().zip(fields.members()) {
+        let attrs = FieldAttrs::from_attrs(&field.attrs)?;
+        if attrs.skip {
+            let unused = [
+                attrs.with.as_ref().map(|v| (v.name_span, "with")),
+                attrs.tolerance.as_ref().map(|v| (v.name_span, "tolerance")),
+                attrs
+                    .relative_tolerance
+                    .as_ref()
+                    .map(|v| (v.name_span, "relative_tolerance")),
+                attrs.rename.as_ref().map(|v| (v.name_span, "rename")),
+                attrs.flatten.span.map(|span| (span, "flatten")),
+                attrs.weight.as_ref().map(|v| (v.name_span, "weight")),
+            ];
+            if let Some((span, name)) = unused.into_iter().flatten().next() {
+                return Err(syn::Error::new(
+                    span,
+                    format!("`{name}` cannot be combined with `skip`"),
+                ));
+            }
+            continue;
+        }
+
+        let named_only = [
+            attrs.rename.as_ref().map(|v| (v.name_span, "rename")),
+            attrs.flatten.span.map(|span| (span, "flatten")),
+        ];
+        if let Member::Unnamed(_) = member {
+            if let Some((span, name)) = named_only.into_iter().flatten().next() {
+                return Err(syn::Error::new(
+                    span,
+                    format!("`{name}` is only supported on named fields"),
+                ));
+            }
+        }
+        if let (true, Some(rename)) = (attrs.flatten.value(), &attrs.rename) {
+            return Err(syn::Error::new(
+                rename.name_span,
+                "`rename` cannot be combined with `flatten`",
+            ));
+        }
+
+        if let Some(NameValue {
+            value:
+                Expr::Unary(
+                    weight @ ExprUnary {
+                        op: UnOp::Neg(_), ..
+                    },
+                ),
+            ..
+        }) = &attrs.weight
+        {
+            if let Expr::Lit(_) = *weight.expr {
+                return Err(syn::Error::new_spanned(
+                    weight,
+                    "`weight` cannot be negative",
+                ));
+            }
+        }
+
+        let diff_member = match &member {
+            Member::Named(_) => member.clone(),
+            Member::Unnamed(index) => Member::Unnamed(Index {
+                index: out.len() as u32,
+                span: index.span,
+            }),
+        };
+        let tolerance = attrs
+            .tolerance
+            .as_ref()
+            .or(attrs.relative_tolerance.as_ref());
+        let strategy = match (attrs.atomic, attrs.with, tolerance) {
+            (true, Some(with), _) => {
+                return Err(syn::Error::new(
+                    with.name_span,
+                    "`with` cannot be combined with `atomic`",
+                ))
+            }
+            (true, None, Some(tolerance)) | (false, Some(_), Some(tolerance)) => {
+                return Err(syn::Error::new(
+                    tolerance.name_span,
+                    "tolerances cannot be combined with `atomic` or `with`",
+                ))
+            }
+            (true, None, None) => Strategy::Atomic,
+            (false, Some(with), None) => Strategy::With(with.value.parse().map_err(|_| {
+                syn::Error::new(with.value.span(), "`with` must be a path to a module")
+            })?),
+            (false, None, Some(_)) => Strategy::Approx {
+                absolute: attrs.tolerance.map(|t| Box::new(t.value)),
+                relative: attrs.relative_tolerance.map(|t| Box::new(t.value)),
+            },
+            (false, None, None) => Strategy::Diffable,
+        };
+
+        let (diffed_ty, derefs) = match strategy {
+            Strategy::With(_) => (field.ty.clone(), 0),
+            _ => peel_references(&field.ty),
+        };
+
+        let mut diff_field = DiffField {
+            member,
+            diff_member,
+            field,
+            diffed_ty,
+            derefs,
+            strategy,
+            rename: attrs.rename.map(|r| r.value),
+            flatten: attrs.flatten.value(),
+            weight: attrs.weight,
+            path_name: String::new(),
+        };
+        diff_field.path_name = diff_field.name();
+        out.push(diff_field);
+    }
+
+    Ok(out)
+}
+
+/// Strips the leading shared references off `ty`, returning the referenced type and their number.
+fn peel_references(mut ty: &Type) -> (Type, usize) {
+    let mut derefs = 0;
+    loop {
+        match ty {
+            Type::Reference(reference) if reference.mutability.is_none() => {
+                ty = &reference.elem;
+                derefs += 1;
+            }
+            Type::Group(group) => ty = &group.elem,
+            Type::Paren(paren) => ty = &paren.elem,
+            _ => return (ty.clone(), derefs),
+        }
+    }
+}
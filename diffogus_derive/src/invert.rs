@@ -0,0 +1,75 @@
+//! Generation of `Invertible` impls for derived diff types.
+
+use crate::bound;
+use crate::changed_variant_ident;
+use crate::field::DiffField;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Generics, Ident, Variant};
+
+/// `Invertible` impl for a generated diff struct, inverting every field diff.
+pub(crate) fn struct_impl(
+    struct_name: &Ident,
+    generics: &Generics,
+    fields: &[DiffField],
+) -> TokenStream2 {
+    let generics =
+        bound::with_repr_bounds(generics, fields, &quote! { ::diffogus::invert::Invertible });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let diff_members = fields.iter().map(|f| &f.diff_member);
+
+    quote! {
+        impl #impl_generics ::diffogus::invert::Invertible for #struct_name #ty_generics #where_clause {
+            fn invert(self) -> Self {
+                Self {
+                    #(#diff_members: ::diffogus::invert::Invertible::invert(self.#diff_members)),*
+                }
+            }
+        }
+    }
+}
+
+/// `Invertible` impl for a generated diff enum.
+pub(crate) fn enum_impl(
+    enum_name: &Ident,
+    generics: &Generics,
+    variants: &[(&Variant, Vec<DiffField>)],
+) -> TokenStream2 {
+    let fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let generics = bound::with_repr_bounds(
+        generics,
+        &fields,
+        &quote! { ::diffogus::invert::Invertible },
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let changed_arms = variants
+        .iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(variant, fields)| {
+            let changed_ident = changed_variant_ident(variant);
+            let diff_members: Vec<_> = fields.iter().map(|f| &f.diff_member).collect();
+            let d: Vec<_> = fields.iter().map(|f| f.binding("__d")).collect();
+
+            quote! {
+                Self::#changed_ident { #(#diff_members: #d),* } => Self::#changed_ident {
+                    #(#diff_members: ::diffogus::invert::Invertible::invert(#d)),*
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics ::diffogus::invert::Invertible for #enum_name #ty_generics #where_clause {
+            fn invert(self) -> Self {
+                match self {
+                    Self::Unchanged => Self::Unchanged,
+                    Self::VariantChanged { old, new } => Self::VariantChanged { old: new, new: old },
+                    #(#changed_arms,)*
+                }
+            }
+        }
+    }
+}
@@ -5,16 +5,31 @@
 //! This crate provides a derive macro to help users implement diffing for their types
 //!
 
+mod bound;
+mod compose;
+mod field;
+mod invert;
+mod merge;
+mod patch;
+mod rebase;
+#[cfg(feature = "serde")]
+mod rename;
+mod similarity;
+mod visit;
+
+use field::{diff_fields, DiffField};
 use heck::ToUpperCamelCase;
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::quote;
-#[cfg(feature = "serde")]
-use quote::ToTokens;
-use structmeta::{NameValue, StructMeta};
+use quote::{quote, ToTokens};
+use structmeta::{Flag, NameArgs, NameValue, StructMeta};
+use syn::parse::Parse;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{parse, Attribute, Data, DeriveInput, Field, Fields, Ident, Type, Visibility};
+use syn::{
+    parse, parse_quote, Attribute, Data, DataEnum, DeriveInput, Fields, Generics, Ident, LitStr,
+    Meta, Path, Variant, Visibility, WherePredicate,
+};
 
 /// Diff derive macro
 #[proc_macro_derive(Diff, attributes(diff))]
@@ -26,127 +41,754 @@ fn filter_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
     attrs.iter().filter(|attr| attr.path().is_ident("diff"))
 }
 
+/// Parses all `#[diff(...)]` attributes in `attrs` as a single list of arguments.
+fn parse_attrs<T: Parse + Default>(attrs: &[Attribute]) -> syn::Result<T> {
+    let mut args = Punctuated::<TokenStream2, Comma>::new();
+    for attr in filter_attrs(attrs) {
+        let tokens = &attr.meta.require_list()?.tokens;
+        if !tokens.is_empty() {
+            args.push(tokens.clone());
+        }
+    }
+
+    if args.is_empty() {
+        Ok(T::default())
+    } else {
+        syn::parse2(args.into_token_stream())
+    }
+}
+
 #[derive(StructMeta, Default)]
 struct StructAttrs {
     vis: Option<NameValue<Visibility>>,
+    bound: Option<NameValue<LitStr>>,
+    atomic: bool,
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    rename_all: Option<NameValue<LitStr>>,
+    name: Option<NameValue<LitStr>>,
+    derive: Option<NameArgs<Vec<Path>>>,
+    attr: Option<NameArgs<Vec<Meta>>>,
+    patch: Flag,
+    invert: Flag,
+    compose: Flag,
+    merge: Flag,
+    rebase: Flag,
+    walk: Flag,
+    similarity: Flag,
+}
+
+impl StructAttrs {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        parse_attrs(attrs)
+    }
+
+    fn bound(&self) -> syn::Result<Option<Punctuated<WherePredicate, Comma>>> {
+        self.bound
+            .as_ref()
+            .map(|b| {
+                b.value
+                    .parse_with(Punctuated::parse_terminated)
+                    .map_err(|err| syn::Error::new(err.span(), format!("invalid `bound`: {err}")))
+            })
+            .transpose()
+    }
+
+    /// Name of the generated diff type, `<Ident>DIff` unless set with `#[diff(name = "...")]`.
+    fn diff_ident(&self, ident: &Ident) -> syn::Result<Ident> {
+        match &self.name {
+            Some(name) => name.value.parse().map_err(|_| {
+                syn::Error::new(name.value.span(), "`name` must be a valid identifier")
+            }),
+            None => Ok(Ident::new(
+                &format!("{}DIff", ident.to_string().to_upper_camel_case()),
+                Span::call_site(),
+            )),
+        }
+    }
+
+    /// Derives and attributes added to the generated diff type with `#[diff(derive(...))]` and
+    /// `#[diff(attr(...))]`.
+    fn extra_attrs(&self) -> TokenStream2 {
+        let derive = self.derive.as_ref().map(|derive| {
+            let paths = &derive.args;
+            quote! { #[derive(#(#paths),*)] }
+        });
+        let attrs = self.attr.iter().flat_map(|attr| &attr.args);
+
+        quote! {
+            #derive
+            #(#[#attrs])*
+        }
+    }
 }
 
 fn derive_diff_or_error(input: TokenStream) -> syn::Result<TokenStream> {
     let input: DeriveInput = parse(input)?;
+    let struct_attrs = StructAttrs::from_attrs(&input.attrs)?;
 
-    let ident = input.ident;
-    let tokens = match input.data {
-        Data::Struct(data_struct) => match &data_struct.fields {
-            Fields::Named(named_fields) => {
-                derive_diff_named_structs(ident, &input.attrs, &named_fields.named)?
-            }
-            _ => todo!("Only structs with named fields are supported right now"),
-        },
-        _ => todo!("Only structs with named fields are supported right now"),
+    let tokens = match &input.data {
+        _ if struct_attrs.atomic => derive_diff_atomic(&input, &struct_attrs)?,
+        Data::Struct(data_struct) => {
+            derive_diff_structs(&input, &struct_attrs, &data_struct.fields)?
+        }
+        Data::Enum(data_enum) => derive_diff_enum(&input, &struct_attrs, data_enum)?,
+        Data::Union(data_union) => {
+            return Err(syn::Error::new(
+                data_union.union_token.span,
+                "Unions are only supported with `#[diff(atomic)]`",
+            ))
+        }
     }
     .into();
 
     Ok(tokens)
 }
 
-fn derive_diff_named_structs(
-    ident: Ident,
-    attrs: &[Attribute],
-    fields: &Punctuated<Field, Comma>,
+fn derive_diff_atomic(
+    input: &DeriveInput,
+    struct_attrs: &StructAttrs,
 ) -> syn::Result<TokenStream2> {
-    let struct_name = Ident::new(
-        &format!("{}DIff", ident.to_string().to_upper_camel_case()),
-        Span::call_site(),
-    );
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
 
-    let struct_attrs = filter_attrs(attrs)
-        .find_map(|a| a.parse_args::<StructAttrs>().ok())
-        .unwrap_or_default();
-    let vis = struct_attrs.vis.map(|f| f.value);
+    // Atomic types are their own diff, so options shaping a generated type don't apply.
+    let unused = [
+        struct_attrs.vis.as_ref().map(|v| (v.name_span, "vis")),
+        struct_attrs
+            .rename_all
+            .as_ref()
+            .map(|v| (v.name_span, "rename_all")),
+        struct_attrs.name.as_ref().map(|v| (v.name_span, "name")),
+        struct_attrs
+            .derive
+            .as_ref()
+            .map(|v| (v.name_span, "derive")),
+        struct_attrs.attr.as_ref().map(|v| (v.name_span, "attr")),
+        struct_attrs.invert.span.map(|span| (span, "invert")),
+        struct_attrs.compose.span.map(|span| (span, "compose")),
+        struct_attrs.rebase.span.map(|span| (span, "rebase")),
+        struct_attrs.walk.span.map(|span| (span, "walk")),
+    ];
+    if let Some((span, name)) = unused.into_iter().flatten().next() {
+        return Err(syn::Error::new(
+            span,
+            format!("`{name}` cannot be combined with `atomic`"),
+        ));
+    }
 
-    let names: Vec<_> = fields.iter().map(|f| &f.ident).collect();
-    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let predicates: Vec<WherePredicate> = match struct_attrs.bound()? {
+        Some(custom) => custom.into_iter().collect(),
+        None if generics.params.is_empty() => vec![],
+        None => vec![parse_quote! {
+            #ident #ty_generics: ::core::cmp::PartialEq
+                + ::core::clone::Clone
+                + ::core::fmt::Debug
+                + for<'__de> ::diffogus::MySerialize<'__de>
+        }],
+    };
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let patch_impl = struct_attrs
+        .patch
+        .value()
+        .then(|| patch::atomic_impl(ident, &generics));
+    let merge_impl = struct_attrs
+        .merge
+        .value()
+        .then(|| merge::atomic_impl(ident, &generics));
+    let similarity_impl = struct_attrs
+        .similarity
+        .value()
+        .then(|| similarity::atomic_impl(ident, &generics));
 
+    Ok(quote! {
+        impl #impl_generics ::diffogus::diff::Diffable for #ident #ty_generics #where_clause {
+            type Repr = ::diffogus::diff::PrimitiveDiff<Self>;
+
+            fn diff(&self, b: &Self) -> Self::Repr {
+                if self == b {
+                    ::diffogus::diff::PrimitiveDiff::Unchanged
+                } else {
+                    ::diffogus::diff::PrimitiveDiff::Changed {
+                        old: ::core::clone::Clone::clone(self),
+                        new: ::core::clone::Clone::clone(b),
+                    }
+                }
+            }
+        }
+
+        #patch_impl
+
+        #merge_impl
+
+        #similarity_impl
+    })
+}
+
+fn derive_diff_structs(
+    input: &DeriveInput,
+    struct_attrs: &StructAttrs,
+    fields: &Fields,
+) -> syn::Result<TokenStream2> {
+    let struct_name = struct_attrs.diff_ident(&input.ident)?;
+
+    #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
+    let mut diff_fields = diff_fields(fields)?;
     #[cfg(feature = "serde")]
-    let diff_struct = generate_diff_struct_serde(&struct_name, &vis, &names, &types, fields)?;
-    #[cfg(not(feature = "serde"))]
-    let diff_struct = generate_diff_struct(&struct_name, &vis, &names, &types)?;
-    let diff_impl = generate_diffable_impl(&ident, &struct_name, &names)?;
+    rename::struct_path_names(&input.attrs, struct_attrs, &mut diff_fields);
+    let generics = bound::with_diffable_bounds(
+        &input.generics,
+        &diff_fields,
+        struct_attrs.bound()?.as_ref(),
+    );
+    // Diffs of borrowed fields are usually owned, so they can outlive the inputs.
+    let borrows = diff_fields
+        .iter()
+        .any(|f| bound::mentions_lifetimes(&f.repr(), &generics));
+    let struct_generics = if borrows {
+        generics.clone()
+    } else {
+        bound::without_lifetimes(&generics)
+    };
+    let vis = struct_attrs.vis.as_ref().map(|f| &f.value);
+
+    let extra_attrs = struct_attrs.extra_attrs();
+    #[cfg(feature = "serde")]
+    let extra_attrs = {
+        let renames = rename::container(&input.attrs, struct_attrs);
+        quote! { #extra_attrs #renames }
+    };
+
+    let diff_struct = generate_diff_struct(
+        &struct_name,
+        vis,
+        &extra_attrs,
+        &struct_generics,
+        fields,
+        &diff_fields,
+    )?;
+    let (_, struct_ty_generics, _) = struct_generics.split_for_impl();
+    // Fields ignored by the diff options are reported with the default, unchanged diff.
+    let mut diffable_generics = bound::with_default_bounds(&generics, &diff_fields);
+    bound::repr_bounds(
+        &mut diffable_generics,
+        &quote! { #struct_name #struct_ty_generics },
+    );
+
+    let diff_impl = generate_diffable_impl(
+        &input.ident,
+        &struct_name,
+        &diffable_generics,
+        &struct_generics,
+        &diff_fields,
+    )?;
+    let patch_impl = struct_attrs
+        .patch
+        .value()
+        .then(|| patch::struct_impl(&input.ident, &diffable_generics, &diff_fields));
+    let merge_impl = struct_attrs
+        .merge
+        .value()
+        .then(|| merge::struct_impl(&input.ident, &diffable_generics, &diff_fields));
+    let compose_impl = struct_attrs
+        .compose
+        .value()
+        .then(|| compose::struct_impl(&struct_name, &struct_generics, &diff_fields));
+    let invert_impl = struct_attrs
+        .invert
+        .value()
+        .then(|| invert::struct_impl(&struct_name, &struct_generics, &diff_fields));
+    let rebase_impl = struct_attrs
+        .rebase
+        .value()
+        .then(|| rebase::struct_impl(&struct_name, &struct_generics, &diff_fields));
+    let visit_impl = struct_attrs
+        .walk
+        .value()
+        .then(|| visit::struct_impl(&struct_name, &struct_generics, &diff_fields));
+    if !struct_attrs.similarity.value() {
+        similarity::check_unused_weights(&diff_fields)?;
+    }
+    let similarity_impl = struct_attrs
+        .similarity
+        .value()
+        .then(|| similarity::struct_impl(&input.ident, &diffable_generics, &diff_fields));
 
     Ok(quote! {
         #diff_struct
 
         #diff_impl
+
+        #patch_impl
+
+        #compose_impl
+
+        #merge_impl
+
+        #invert_impl
+
+        #rebase_impl
+
+        #visit_impl
+
+        #similarity_impl
     })
 }
 
 fn generate_diffable_impl(
     ident: &Ident,
     struct_name: &Ident,
-    names: &Vec<&Option<Ident>>,
+    diffable_generics: &Generics,
+    struct_generics: &Generics,
+    fields: &[DiffField],
 ) -> syn::Result<TokenStream2> {
+    let (impl_generics, ty_generics, diffable_where) = diffable_generics.split_for_impl();
+    let (struct_impl_generics, struct_ty_generics, struct_where) = struct_generics.split_for_impl();
+
+    let a = Ident::new("__a", Span::call_site());
+    let b = Ident::new("__b", Span::call_site());
+    let diffs = fields.iter().map(|f| {
+        let member = &f.member;
+        let diff_member = &f.diff_member;
+        let diff = f.diff_unless_ignored(&a, &b);
+        quote! {
+            #diff_member: {
+                let (#a, #b) = (&self.#member, &b.#member);
+                #diff
+            }
+        }
+    });
+
+    let unused = fields.is_empty().then(|| quote! { let _ = options; });
+    let diff_members: Vec<_> = fields.iter().map(|f| &f.diff_member).collect();
+    let is_changed = if diff_members.is_empty() {
+        quote! { false }
+    } else {
+        quote! { #(::diffogus::diff::Changeable::is_changed(&self.#diff_members)) || * }
+    };
+
     Ok(quote! {
-        impl ::diffogus::diff::Diffable for #ident {
-            type Repr = #struct_name;
+        impl #impl_generics ::diffogus::diff::Diffable for #ident #ty_generics #diffable_where {
+            type Repr = #struct_name #struct_ty_generics;
 
             fn diff(&self, b: &Self) -> Self::Repr {
+                self.diff_with(b, &::diffogus::diff::DiffOptions::default())
+            }
+
+            fn diff_with(&self, b: &Self, options: &::diffogus::diff::DiffOptions) -> Self::Repr {
+                #unused
                 #struct_name {
-                    #(#names: self.#names.diff(&b.#names)),*
+                    #(#diffs),*
                 }
             }
         }
 
-        impl ::diffogus::diff::Changeable for #struct_name {
+        impl #struct_impl_generics ::diffogus::diff::Changeable for #struct_name #struct_ty_generics #struct_where {
             fn is_changed(&self) -> bool {
-                #(self.#names.is_changed()) || *
+                #is_changed
             }
         }
     })
 }
 
-#[cfg(feature = "serde")]
-fn generate_diff_struct_serde(
+fn generate_diff_struct(
     struct_name: &Ident,
-    vis: &Option<Visibility>,
-    names: &Vec<&Option<Ident>>,
-    types: &Vec<&Type>,
-    fields: &Punctuated<Field, Comma>,
+    vis: Option<&Visibility>,
+    extra_attrs: &TokenStream2,
+    generics: &Generics,
+    fields: &Fields,
+    diff_fields: &[DiffField],
 ) -> syn::Result<TokenStream2> {
-    let skips: Vec<_> = fields
+    let where_clause = &generics.where_clause;
+
+    #[cfg(feature = "serde")]
+    let derives = {
+        let serde_bound = bound::serde_bound(generics, diff_fields);
+        quote! {
+            #[derive(Debug, serde::Serialize, serde::Deserialize)]
+            #serde_bound
+            #extra_attrs
+        }
+    };
+    #[cfg(not(feature = "serde"))]
+    let derives = quote! { #[derive(Debug)] #extra_attrs };
+
+    let default_generics = bound::with_default_bounds(generics, diff_fields);
+    let (impl_generics, ty_generics, default_where) = default_generics.split_for_impl();
+    let diff_members = diff_fields.iter().map(|f| &f.diff_member);
+    let default_impl = quote! {
+        impl #impl_generics ::core::default::Default for #struct_name #ty_generics #default_where {
+            fn default() -> Self {
+                Self {
+                    #(#diff_members: ::core::default::Default::default()),*
+                }
+            }
+        }
+    };
+
+    let reprs = diff_fields.iter().map(DiffField::repr);
+    let diff_struct = match fields {
+        Fields::Named(_) => {
+            let names = diff_fields.iter().map(|f| &f.diff_member);
+            #[cfg(feature = "serde")]
+            let skips: Vec<_> = diff_fields.iter().map(serde_field_attrs).collect();
+            #[cfg(not(feature = "serde"))]
+            let skips = diff_fields.iter().map(|_| quote! {});
+
+            quote! {
+                #derives
+                #vis struct #struct_name #generics #where_clause {
+                    #(
+                        #skips
+                        #vis #names: #reprs
+                    ),*
+                }
+            }
+        }
+        Fields::Unnamed(_) => {
+            // Newtypes serialize exactly like the diff of the wrapped value.
+            #[cfg(feature = "serde")]
+            let transparent = (diff_fields.len() == 1).then(|| quote! { #[serde(transparent)] });
+            #[cfg(not(feature = "serde"))]
+            let transparent = quote! {};
+
+            quote! {
+                #derives
+                #transparent
+                #vis struct #struct_name #generics (
+                    #(#vis #reprs),*
+                ) #where_clause;
+            }
+        }
+        Fields::Unit => quote! {
+            #derives
+            #vis struct #struct_name #generics #where_clause;
+        },
+    };
+
+    Ok(quote! {
+        #diff_struct
+
+        #default_impl
+    })
+}
+
+#[cfg(feature = "serde")]
+fn serde_field_attrs(field: &DiffField) -> TokenStream2 {
+    if field.flatten {
+        // Unchanged fields of the nested diff are skipped on their own.
+        return quote! { #[serde(flatten)] };
+    }
+
+    let path = format!(
+        "<{} as ::diffogus::diff::Changeable>::is_unchanged",
+        field.repr()
+    );
+    let rename = rename::field(field);
+    quote! {
+        #[serde(default, skip_serializing_if = #path)]
+        #rename
+    }
+}
+
+fn derive_diff_enum(
+    input: &DeriveInput,
+    struct_attrs: &StructAttrs,
+    data: &DataEnum,
+) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    if data.variants.is_empty() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "Enums without variants are not supported",
+        ));
+    }
+    // `VariantChanged` holds the compared values, so the diff would borrow from the inputs.
+    if let Some(lifetime) = input.generics.lifetimes().next() {
+        return Err(syn::Error::new_spanned(
+            lifetime,
+            "enums with lifetime parameters are not supported, as `VariantChanged` would borrow the compared values",
+        ));
+    }
+
+    let enum_name = struct_attrs.diff_ident(ident)?;
+
+    let variants: Vec<_> = data
+        .variants
+        .iter()
+        .map(|v| {
+            if let Some(attr) = filter_attrs(&v.attrs).next() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`#[diff(...)]` is not supported on enum variants",
+                ));
+            }
+            if v.ident == "Variant" && !v.fields.is_empty() {
+                return Err(syn::Error::new(
+                    v.ident.span(),
+                    "a variant named `Variant` with fields clashes with the generated `VariantChanged` variant",
+                ));
+            }
+            #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
+            let mut fields = diff_fields(&v.fields)?;
+            #[cfg(feature = "serde")]
+            rename::variant_path_names(&input.attrs, struct_attrs, v, &mut fields);
+            Ok((v, fields))
+        })
+        .collect::<syn::Result<_>>()?;
+    let all_fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let generics =
+        bound::with_diffable_bounds(&input.generics, &all_fields, struct_attrs.bound()?.as_ref());
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let vis = struct_attrs.vis.as_ref().map(|f| &f.value);
+
+    let diff_variants: Vec<_> = variants
         .iter()
-        .map(|f| {
-            let ty = format!(
-                "<<{} as ::diffogus::diff::Diffable>::Repr as ::diffogus::diff::Changeable>::is_unchanged",
-                &f.ty.to_token_stream()
-            );
-            quote! { #[serde(default, skip_serializing_if = #ty)] }
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(v, fields)| {
+            #[cfg(feature = "serde")]
+            let renames = rename::variant(&input.attrs, struct_attrs, v);
+            #[cfg(not(feature = "serde"))]
+            let renames = quote! {};
+            generate_diff_variant(v, &renames, fields)
         })
         .collect();
 
+    #[cfg(feature = "serde")]
+    let enum_attrs = {
+        let serde_bound = bound::serde_bound(&generics, &all_fields);
+        quote! {
+            #[derive(Default, Debug, serde::Serialize, serde::Deserialize)]
+            #[serde(rename_all = "snake_case", tag = "type", content = "value")]
+            #serde_bound
+        }
+    };
+    #[cfg(not(feature = "serde"))]
+    let enum_attrs = quote! {
+        #[derive(Default, Debug)]
+    };
+
+    // Fields ignored by the diff options are reported with the default, unchanged diff.
+    let fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let mut diffable_generics = bound::with_default_bounds(&generics, &fields);
+    bound::repr_bounds(&mut diffable_generics, &quote! { #enum_name #ty_generics });
+    if !generics.params.is_empty() {
+        diffable_generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { #ident #ty_generics: ::std::clone::Clone });
+    }
+
+    let extra_attrs = struct_attrs.extra_attrs();
+    let diff_impl =
+        generate_enum_diffable_impl(ident, &enum_name, &generics, &diffable_generics, &variants)?;
+    let patch_impl = struct_attrs
+        .patch
+        .value()
+        .then(|| patch::enum_impl(ident, &enum_name, &diffable_generics, &variants));
+    let merge_impl = struct_attrs
+        .merge
+        .value()
+        .then(|| merge::enum_impl(ident, &diffable_generics, &variants));
+    let compose_impl = match struct_attrs.compose.span {
+        // Composing a changed variant with another diff applies or inverts the other diff.
+        Some(span) if !struct_attrs.patch.value() || !struct_attrs.invert.value() => {
+            return Err(syn::Error::new(
+                span,
+                "`compose` on enums requires `patch` and `invert`",
+            ))
+        }
+        Some(_) => Some(compose::enum_impl(
+            &enum_name,
+            &diffable_generics,
+            &variants,
+        )),
+        None => None,
+    };
+    let invert_impl = struct_attrs
+        .invert
+        .value()
+        .then(|| invert::enum_impl(&enum_name, &generics, &variants));
+    let rebase_impl = struct_attrs
+        .rebase
+        .value()
+        .then(|| rebase::enum_impl(&enum_name, &diffable_generics, &variants));
+    let visit_impl = struct_attrs
+        .walk
+        .value()
+        .then(|| visit::enum_impl(ident, &enum_name, &diffable_generics, &variants));
+    if !struct_attrs.similarity.value() {
+        similarity::check_unused_weights(&fields)?;
+    }
+    let similarity_impl = struct_attrs
+        .similarity
+        .value()
+        .then(|| similarity::enum_impl(ident, &diffable_generics, &variants));
+
     Ok(quote! {
-        #[derive(Default, Debug, serde::Serialize, serde::Deserialize)]
-        #vis struct #struct_name {
-            #(
-                #skips
-                #vis #names: <#types as ::diffogus::diff::Diffable>::Repr
-            ),*
+        #enum_attrs
+        #extra_attrs
+        #vis enum #enum_name #generics #where_clause {
+            /// Indicates that the value has not changed.
+            #[default]
+            Unchanged,
+            /// Indicates that the enum variant has changed.
+            VariantChanged {
+                /// Field holding the old value.
+                old: #ident #ty_generics,
+                /// Field holding the new value.
+                new: #ident #ty_generics,
+            },
+            #(#diff_variants),*
         }
+
+        #diff_impl
+
+        #patch_impl
+
+        #compose_impl
+
+        #merge_impl
+
+        #invert_impl
+
+        #rebase_impl
+
+        #visit_impl
+
+        #similarity_impl
     })
 }
 
-#[cfg(not(feature = "serde"))]
-fn generate_diff_struct(
-    struct_name: &Ident,
-    vis: &Option<Visibility>,
-    names: &Vec<&Option<Ident>>,
-    types: &Vec<&Type>,
+fn changed_variant_ident(variant: &Variant) -> Ident {
+    Ident::new(&format!("{}Changed", variant.ident), variant.ident.span())
+}
+
+fn generate_diff_variant(
+    variant: &Variant,
+    renames: &TokenStream2,
+    fields: &[DiffField],
+) -> TokenStream2 {
+    let name = changed_variant_ident(variant);
+    let reprs = fields.iter().map(DiffField::repr);
+
+    match &variant.fields {
+        Fields::Named(_) => {
+            let names = fields.iter().map(|f| &f.diff_member);
+            #[cfg(feature = "serde")]
+            let skips: Vec<_> = fields.iter().map(serde_field_attrs).collect();
+            #[cfg(not(feature = "serde"))]
+            let skips = fields.iter().map(|_| quote! {});
+
+            quote! {
+                #renames
+                #name {
+                    #(
+                        #skips
+                        #names: #reprs
+                    ),*
+                }
+            }
+        }
+        _ => quote! { #name(#(#reprs),*) },
+    }
+}
+
+fn generate_enum_diffable_impl(
+    ident: &Ident,
+    enum_name: &Ident,
+    generics: &Generics,
+    diffable_generics: &Generics,
+    variants: &[(&Variant, Vec<DiffField>)],
 ) -> syn::Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let diffable_where = &diffable_generics.where_clause;
+    let unused = variants
+        .iter()
+        .all(|(_, fields)| fields.is_empty())
+        .then(|| quote! { let _ = options; });
+
+    let mut diff_arms = vec![];
+    let mut changed_arms = vec![];
+
+    for (variant, fields) in variants {
+        let variant_ident = &variant.ident;
+        let changed_ident = changed_variant_ident(variant);
+
+        if fields.is_empty() {
+            diff_arms.push(quote! {
+                (Self::#variant_ident { .. }, Self::#variant_ident { .. }) => #enum_name::Unchanged
+            });
+            continue;
+        }
+
+        let members: Vec<_> = fields.iter().map(|f| &f.member).collect();
+        let diff_members: Vec<_> = fields.iter().map(|f| &f.diff_member).collect();
+        let a: Vec<_> = fields.iter().map(|f| f.binding("__a")).collect();
+        let b: Vec<_> = fields.iter().map(|f| f.binding("__b")).collect();
+        let diffs = fields
+            .iter()
+            .zip(a.iter().zip(&b))
+            .map(|(f, (a, b))| f.diff_unless_ignored(a, b));
+
+        diff_arms.push(quote! {
+            (
+                Self::#variant_ident { #(#members: #a,)* .. },
+                Self::#variant_ident { #(#members: #b,)* .. },
+            ) => {
+                let diff = #enum_name::#changed_ident {
+                    #(#diff_members: #diffs),*
+                };
+                if ::diffogus::diff::Changeable::is_changed(&diff) {
+                    diff
+                } else {
+                    #enum_name::Unchanged
+                }
+            }
+        });
+        changed_arms.push(quote! {
+            Self::#changed_ident { #(#diff_members: #a),* } => {
+                #(::diffogus::diff::Changeable::is_changed(#a)) || *
+            }
+        });
+    }
+
     Ok(quote! {
-        #[derive(Default, Debug)]
-        #vis struct #struct_name {
-            #(
-                #vis #names: <#types as ::diffogus::diff::Diffable>::Repr
-            ),*
+        impl #impl_generics ::diffogus::diff::Diffable for #ident #ty_generics #diffable_where {
+            type Repr = #enum_name #ty_generics;
+
+            fn diff(&self, b: &Self) -> Self::Repr {
+                self.diff_with(b, &::diffogus::diff::DiffOptions::default())
+            }
+
+            fn diff_with(&self, b: &Self, options: &::diffogus::diff::DiffOptions) -> Self::Repr {
+                #unused
+                #[allow(unreachable_patterns)]
+                match (self, b) {
+                    #(#diff_arms,)*
+                    _ => #enum_name::VariantChanged {
+                        old: ::std::clone::Clone::clone(self),
+                        new: ::std::clone::Clone::clone(b),
+                    },
+                }
+            }
+        }
+
+        impl #impl_generics ::diffogus::diff::Changeable for #enum_name #ty_generics #where_clause {
+            fn is_changed(&self) -> bool {
+                match self {
+                    Self::Unchanged => false,
+                    Self::VariantChanged { .. } => true,
+                    #(#changed_arms),*
+                }
+            }
         }
     })
 }
@@ -0,0 +1,127 @@
+//! Generation of `Mergeable` impls for derived types.
+
+use crate::bound;
+use crate::field::DiffField;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_quote, Generics, Ident, Variant};
+
+/// Statements merging every field into `ours`, read from the bindings returned by `bind`.
+fn merge_fields(
+    fields: &[DiffField],
+    bind: impl Fn(&DiffField) -> [TokenStream2; 3],
+) -> TokenStream2 {
+    let merges = fields.iter().map(|f| {
+        let [ours, base, theirs] = bind(f);
+        let merge = f.scoped("merger", f.merge(&ours, &base, &theirs));
+        quote! { #merge; }
+    });
+    quote! { #(#merges)* }
+}
+
+/// `Mergeable` impl for a struct, merging every field on its own.
+pub(crate) fn struct_impl(
+    ident: &Ident,
+    generics: &Generics,
+    fields: &[DiffField],
+) -> TokenStream2 {
+    let generics = bound::with_merge_bounds(generics, fields);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let merges = merge_fields(fields, |f| {
+        let member = &f.member;
+        [
+            quote! { &mut self.#member },
+            quote! { &base.#member },
+            quote! { &theirs.#member },
+        ]
+    });
+    let unused = fields
+        .is_empty()
+        .then(|| quote! { let _ = (base, theirs, merger); });
+
+    quote! {
+        impl #impl_generics ::diffogus::merge::Mergeable for #ident #ty_generics #where_clause {
+            fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut ::diffogus::merge::Merger) {
+                #unused
+                #merges
+            }
+        }
+    }
+}
+
+/// `Mergeable` impl for an enum. Values of the same variant on all sides are merged field by
+/// field, otherwise the whole value is merged as one.
+pub(crate) fn enum_impl(
+    ident: &Ident,
+    generics: &Generics,
+    variants: &[(&Variant, Vec<DiffField>)],
+) -> TokenStream2 {
+    let fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let mut generics = bound::with_merge_bounds(generics, &fields);
+    if !generics.params.is_empty() {
+        let (_, ty_generics, _) = generics.split_for_impl();
+        let predicate = parse_quote! { #ident #ty_generics: ::core::fmt::Debug + 'static };
+        generics.make_where_clause().predicates.push(predicate);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let variant_arms = variants
+        .iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(variant, fields)| {
+            let variant_ident = &variant.ident;
+            let members: Vec<_> = fields.iter().map(|f| &f.member).collect();
+            let [o, b, t] = ["__o", "__b", "__t"]
+                .map(|prefix| fields.iter().map(|f| f.binding(prefix)).collect::<Vec<_>>());
+            let merges = merge_fields(fields, |f| {
+                ["__o", "__b", "__t"].map(|prefix| {
+                    let binding = f.binding(prefix);
+                    quote! { #binding }
+                })
+            });
+
+            quote! {
+                (
+                    Self::#variant_ident { #(#members: #o,)* .. },
+                    Self::#variant_ident { #(#members: #b,)* .. },
+                    Self::#variant_ident { #(#members: #t,)* .. },
+                ) => {
+                    #merges
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics ::diffogus::merge::Mergeable for #ident #ty_generics #where_clause {
+            fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut ::diffogus::merge::Merger) {
+                match (self, base, theirs) {
+                    #(#variant_arms,)*
+                    (ours, base, theirs) => merger.merge_leaf(ours, base, theirs),
+                }
+            }
+        }
+    }
+}
+
+/// `Mergeable` impl for a type diffed as a whole with `#[diff(atomic)]`.
+pub(crate) fn atomic_impl(ident: &Ident, generics: &Generics) -> TokenStream2 {
+    let mut generics = generics.clone();
+    if !generics.params.is_empty() {
+        let (_, ty_generics, _) = generics.split_for_impl();
+        let predicate = parse_quote! { #ident #ty_generics: 'static };
+        generics.make_where_clause().predicates.push(predicate);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::diffogus::merge::Mergeable for #ident #ty_generics #where_clause {
+            fn merge(&mut self, base: &Self, theirs: &Self, merger: &mut ::diffogus::merge::Merger) {
+                merger.merge_leaf_by(self, base, theirs, |a, b| a != b);
+            }
+        }
+    }
+}
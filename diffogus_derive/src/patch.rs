@@ -0,0 +1,129 @@
+//! Generation of `Patchable` impls for derived diff types.
+
+use crate::field::DiffField;
+use crate::{bound, changed_variant_ident};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Generics, Ident, Variant};
+
+/// `Patchable` impl for a struct, applying the diff of every field in place.
+pub(crate) fn struct_impl(
+    ident: &Ident,
+    generics: &Generics,
+    fields: &[DiffField],
+) -> TokenStream2 {
+    let generics = bound::with_patch_bounds(generics, fields);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let applies = fields.iter().map(|f| {
+        let (member, diff_member) = (&f.member, &f.diff_member);
+        f.apply(
+            &quote! { &mut self.#member },
+            &quote! { &diff.#diff_member },
+        )
+    });
+    let unused = fields.is_empty().then(|| quote! { let _ = diff; });
+
+    quote! {
+        impl #impl_generics ::diffogus::patch::Patchable for #ident #ty_generics #where_clause {
+            fn apply(
+                &mut self,
+                diff: &<Self as ::diffogus::diff::Diffable>::Repr,
+            ) -> ::core::result::Result<(), ::diffogus::patch::PatchError> {
+                #unused
+                #(#applies;)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    }
+}
+
+/// `Patchable` impl for an enum.
+///
+/// Changes within a variant are applied to the fields in place, while a changed variant replaces
+/// the whole value.
+pub(crate) fn enum_impl(
+    ident: &Ident,
+    enum_name: &Ident,
+    generics: &Generics,
+    variants: &[(&Variant, Vec<DiffField>)],
+) -> TokenStream2 {
+    let fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let generics = bound::with_patch_bounds(generics, &fields);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let changed_arms = variants
+        .iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(variant, fields)| {
+            let variant_ident = &variant.ident;
+            let changed_ident = changed_variant_ident(variant);
+            let members = fields.iter().map(|f| &f.member);
+            let diff_members = fields.iter().map(|f| &f.diff_member);
+            let a: Vec<_> = fields.iter().map(|f| f.binding("__a")).collect();
+            let d: Vec<_> = fields.iter().map(|f| f.binding("__d")).collect();
+            let applies = fields
+                .iter()
+                .zip(a.iter().zip(&d))
+                .map(|(f, (a, d))| f.apply(&quote! { #a }, &quote! { #d }));
+
+            quote! {
+                #enum_name::#changed_ident { #(#diff_members: #d),* } => {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        Self::#variant_ident { #(#members: #a,)* .. } => {
+                            #(#applies;)*
+                        }
+                        _ => return ::core::result::Result::Err(::diffogus::patch::PatchError::Mismatch),
+                    }
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics ::diffogus::patch::Patchable for #ident #ty_generics #where_clause {
+            fn apply(
+                &mut self,
+                diff: &<Self as ::diffogus::diff::Diffable>::Repr,
+            ) -> ::core::result::Result<(), ::diffogus::patch::PatchError> {
+                match diff {
+                    #enum_name::Unchanged => {}
+                    #enum_name::VariantChanged { old, new } => {
+                        let diff = ::diffogus::diff::Diffable::diff(&*self, old);
+                        if ::diffogus::diff::Changeable::is_changed(&diff) {
+                            return ::core::result::Result::Err(::diffogus::patch::PatchError::Mismatch);
+                        }
+                        *self = ::std::clone::Clone::clone(new);
+                    }
+                    #(#changed_arms)*
+                }
+                ::core::result::Result::Ok(())
+            }
+        }
+    }
+}
+
+/// `Patchable` impl for a type diffed as a whole with `#[diff(atomic)]`.
+pub(crate) fn atomic_impl(ident: &Ident, generics: &Generics) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::diffogus::patch::Patchable for #ident #ty_generics #where_clause {
+            fn apply(
+                &mut self,
+                diff: &<Self as ::diffogus::diff::Diffable>::Repr,
+            ) -> ::core::result::Result<(), ::diffogus::patch::PatchError> {
+                if let ::diffogus::diff::PrimitiveDiff::Changed { old, new } = diff {
+                    if self != old {
+                        return ::core::result::Result::Err(::diffogus::patch::PatchError::Mismatch);
+                    }
+                    *self = ::core::clone::Clone::clone(new);
+                }
+                ::core::result::Result::Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Generation of `Rebasable` impls for derived diff types.
+
+use crate::bound;
+use crate::changed_variant_ident;
+use crate::field::DiffField;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Generics, Ident, Variant};
+
+/// Expression rebasing the field diff `diff` onto `onto`, under the field's name.
+fn rebase_field(field: &DiffField, diff: &Ident, onto: &TokenStream2) -> TokenStream2 {
+    field.scoped(
+        "rebaser",
+        quote! { ::diffogus::rebase::Rebasable::rebase(#diff, #onto, rebaser) },
+    )
+}
+
+/// `Rebasable` impl for a generated diff struct, rebasing every field diff.
+pub(crate) fn struct_impl(
+    struct_name: &Ident,
+    generics: &Generics,
+    fields: &[DiffField],
+) -> TokenStream2 {
+    let generics =
+        bound::with_repr_bounds(generics, fields, &quote! { ::diffogus::rebase::Rebasable });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let diff_members: Vec<_> = fields.iter().map(|f| &f.diff_member).collect();
+    let d: Vec<_> = fields.iter().map(|f| f.binding("__d")).collect();
+    let rebases = fields.iter().zip(&d).map(|(f, d)| {
+        let diff_member = &f.diff_member;
+        rebase_field(f, d, &quote! { &onto.#diff_member })
+    });
+    let unused = fields
+        .is_empty()
+        .then(|| quote! { let _ = (onto, rebaser); });
+
+    quote! {
+        impl #impl_generics ::diffogus::rebase::Rebasable for #struct_name #ty_generics #where_clause {
+            fn rebase(self, onto: &Self, rebaser: &mut ::diffogus::rebase::Rebaser) -> Self {
+                #unused
+                let Self { #(#diff_members: #d),* } = self;
+                Self {
+                    #(#diff_members: #rebases),*
+                }
+            }
+        }
+    }
+}
+
+/// `Rebasable` impl for a generated diff enum. Changes to the same variant are rebased field by
+/// field, while a changed variant conflicts with any other change.
+pub(crate) fn enum_impl(
+    enum_name: &Ident,
+    generics: &Generics,
+    variants: &[(&Variant, Vec<DiffField>)],
+) -> TokenStream2 {
+    let fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let generics =
+        bound::with_repr_bounds(generics, &fields, &quote! { ::diffogus::rebase::Rebasable });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let changed_arms = variants
+        .iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(variant, fields)| {
+            let changed_ident = changed_variant_ident(variant);
+            let diff_members: Vec<_> = fields.iter().map(|f| &f.diff_member).collect();
+            let d: Vec<_> = fields.iter().map(|f| f.binding("__d")).collect();
+            let o: Vec<_> = fields.iter().map(|f| f.binding("__o")).collect();
+            let rebases = fields
+                .iter()
+                .zip(d.iter().zip(&o))
+                .map(|(f, (d, o))| rebase_field(f, d, &quote! { #o }));
+
+            quote! {
+                (
+                    Self::#changed_ident { #(#diff_members: #d),* },
+                    Self::#changed_ident { #(#diff_members: #o),* },
+                ) => Self::#changed_ident {
+                    #(#diff_members: #rebases),*
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics ::diffogus::rebase::Rebasable for #enum_name #ty_generics #where_clause {
+            fn rebase(self, onto: &Self, rebaser: &mut ::diffogus::rebase::Rebaser) -> Self {
+                #[allow(unreachable_patterns)]
+                let diff = match (self, onto) {
+                    (diff, Self::Unchanged) => diff,
+                    (Self::Unchanged, _) => Self::Unchanged,
+                    #(#changed_arms,)*
+                    (Self::VariantChanged { new, .. }, Self::VariantChanged { new: onto_new, .. }) => {
+                        let diff = ::diffogus::diff::Diffable::diff(&new, onto_new);
+                        if ::diffogus::diff::Changeable::is_changed(&diff) {
+                            rebaser.reject(::diffogus::rebase::RejectReason::Conflict);
+                        }
+                        Self::Unchanged
+                    }
+                    _ => {
+                        rebaser.reject(::diffogus::rebase::RejectReason::Conflict);
+                        Self::Unchanged
+                    }
+                };
+
+                if ::diffogus::diff::Changeable::is_changed(&diff) {
+                    diff
+                } else {
+                    Self::Unchanged
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Forwarding of serde renames from the input type onto the generated diff type.
+
+use crate::field::DiffField;
+use crate::StructAttrs;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{Attribute, Expr, ExprLit, Ident, Lit, LitStr, Member, Meta, Variant};
+
+/// Collects the `#[serde(...)]` items called `name` from `attrs`, renamed to `as_name`.
+fn serde_items(attrs: &[Attribute], name: &str, as_name: &str) -> Vec<Meta> {
+    let as_name = Ident::new(as_name, Span::call_site());
+
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .filter(|meta| meta.path().is_ident(name))
+        .map(|mut meta| {
+            match &mut meta {
+                Meta::Path(path) => *path = as_name.clone().into(),
+                Meta::List(list) => list.path = as_name.clone().into(),
+                Meta::NameValue(name_value) => name_value.path = as_name.clone().into(),
+            }
+            meta
+        })
+        .collect()
+}
+
+/// Serialization side of the `#[serde(...)]` item called `name` in `attrs`, either
+/// `name = "..."` or `name(serialize = "...")`.
+fn serialized(attrs: &[Attribute], name: &str) -> Option<String> {
+    let lit_str = |meta: &Meta| match meta {
+        Meta::NameValue(name_value) => match &name_value.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(value),
+                ..
+            }) => Some(value.value()),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    serde_items(attrs, name, name)
+        .iter()
+        .find_map(|meta| match meta {
+            Meta::List(list) => list
+                .parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)
+                .ok()?
+                .iter()
+                .filter(|meta| meta.path().is_ident("serialize"))
+                .find_map(lit_str),
+            _ => lit_str(meta),
+        })
+}
+
+/// Applies the serde `rename_all` rule `rule` to the field name `name`, leaving it as it is
+/// for unknown rules.
+fn apply_rule(rule: &str, name: &str) -> String {
+    let pascal_case = || {
+        let mut pascal = String::new();
+        let mut capitalize = true;
+        for ch in name.chars() {
+            if ch == '_' {
+                capitalize = true;
+            } else if capitalize {
+                pascal.push(ch.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                pascal.push(ch);
+            }
+        }
+        pascal
+    };
+
+    match rule {
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+        "PascalCase" => pascal_case(),
+        "camelCase" => {
+            let pascal = pascal_case();
+            let mut chars = pascal.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                None => pascal,
+            }
+        }
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.to_ascii_uppercase().replace('_', "-"),
+        _ => name.to_string(),
+    }
+}
+
+/// Sets the path names of `fields` to the names their diffs are serialized under, given the
+/// `rename_all` rule of their container.
+fn path_names(fields: &mut [DiffField], rule: Option<String>) {
+    for field in fields {
+        let Member::Named(ident) = &field.member else {
+            continue;
+        };
+        let rename = field.rename.as_ref().map(LitStr::value);
+        field.path_name = rename
+            .or_else(|| serialized(&field.field.attrs, "rename"))
+            .unwrap_or_else(|| {
+                let name = ident.unraw().to_string();
+                match &rule {
+                    Some(rule) => apply_rule(rule, &name),
+                    None => name,
+                }
+            });
+    }
+}
+
+/// Sets the path names of the fields of a struct, renamed like [`container`] and [`field`].
+pub(crate) fn struct_path_names(
+    attrs: &[Attribute],
+    struct_attrs: &StructAttrs,
+    fields: &mut [DiffField],
+) {
+    let rule = match &struct_attrs.rename_all {
+        Some(rename_all) => Some(rename_all.value.value()),
+        None => serialized(attrs, "rename_all"),
+    };
+    path_names(fields, rule);
+}
+
+/// Sets the path names of the fields of an enum variant, renamed like [`variant`] and [`field`].
+pub(crate) fn variant_path_names(
+    attrs: &[Attribute],
+    struct_attrs: &StructAttrs,
+    variant: &Variant,
+    fields: &mut [DiffField],
+) {
+    let rule = match &struct_attrs.rename_all {
+        Some(rename_all) => Some(rename_all.value.value()),
+        None => serialized(&variant.attrs, "rename_all")
+            .or_else(|| serialized(attrs, "rename_all_fields")),
+    };
+    path_names(fields, rule);
+}
+
+/// `#[serde(name = ...)]` attribute set by a `#[diff(name = ...)]` override.
+fn overridden(name: &str, value: &LitStr) -> TokenStream2 {
+    let name = Ident::new(name, Span::call_site());
+    quote! { #[serde(#name = #value)] }
+}
+
+/// Renames for the generated diff struct, forwarded from the input's `rename_all`.
+pub(crate) fn container(attrs: &[Attribute], struct_attrs: &StructAttrs) -> TokenStream2 {
+    match &struct_attrs.rename_all {
+        Some(rename_all) => overridden("rename_all", &rename_all.value),
+        None => {
+            let items = serde_items(attrs, "rename_all", "rename_all");
+            quote! { #(#[serde(#items)])* }
+        }
+    }
+}
+
+/// Renames for the fields of a `<Variant>Changed` variant, forwarded from the variant's
+/// `rename_all` or from the enum's `rename_all_fields`.
+pub(crate) fn variant(
+    attrs: &[Attribute],
+    struct_attrs: &StructAttrs,
+    variant: &Variant,
+) -> TokenStream2 {
+    if let Some(rename_all) = &struct_attrs.rename_all {
+        return overridden("rename_all", &rename_all.value);
+    }
+
+    let mut items = serde_items(&variant.attrs, "rename_all", "rename_all");
+    if items.is_empty() {
+        items = serde_items(attrs, "rename_all_fields", "rename_all");
+    }
+    quote! { #(#[serde(#items)])* }
+}
+
+/// Rename of a single field, forwarded from the input field's `rename`.
+pub(crate) fn field(field: &DiffField) -> TokenStream2 {
+    match &field.rename {
+        Some(rename) => overridden("rename", rename),
+        None => {
+            let items = serde_items(&field.field.attrs, "rename", "rename");
+            quote! { #(#[serde(#items)])* }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Generation of `Similar` impls for derived types.
+
+use crate::bound;
+use crate::field::DiffField;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{Generics, Ident, Variant};
+
+/// Expressions scoring `fields` as `(similarity, distance)`, weighing every field by its weight.
+/// `bind` returns the references to both values of a field.
+fn score_fields(
+    fields: &[DiffField],
+    bind: impl Fn(&DiffField) -> [TokenStream2; 2],
+) -> (TokenStream2, TokenStream2) {
+    if fields.is_empty() {
+        return (quote! { 1.0 }, quote! { 0.0 });
+    }
+
+    let (a, b) = (
+        Ident::new("__a", Span::call_site()),
+        Ident::new("__b", Span::call_site()),
+    );
+    let (similarities, distances): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .map(|f| {
+            let [a_ref, b_ref] = bind(f);
+            let weight = f.weight();
+            let (similarity, distance) = f.similarity(&a, &b);
+            (
+                quote! {{
+                    let (#a, #b) = (#a_ref, #b_ref);
+                    (#weight, #weight * #similarity)
+                }},
+                quote! {{
+                    let (#a, #b) = (#a_ref, #b_ref);
+                    #weight * #distance
+                }},
+            )
+        })
+        .unzip();
+
+    (
+        quote! {{
+            let (weights, score) = [#(#similarities),*]
+                .into_iter()
+                .fold((0.0_f64, 0.0_f64), |(w, s), (weight, score)| (w + weight, s + score));
+            if weights == 0.0 { 1.0 } else { score / weights }
+        }},
+        quote! { 0.0_f64 #(+ #distances)* },
+    )
+}
+
+/// Rejects `#[diff(weight = ...)]` on the fields of a type without a `Similar` impl.
+pub(crate) fn check_unused_weights(fields: &[DiffField]) -> syn::Result<()> {
+    match fields.iter().find_map(|f| f.weight.as_ref()) {
+        Some(weight) => Err(syn::Error::new(
+            weight.name_span,
+            "`weight` requires `#[diff(similarity)]` on the type",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// `Similar` impl for a struct, averaging the similarities of its fields by their weights.
+pub(crate) fn struct_impl(
+    ident: &Ident,
+    generics: &Generics,
+    fields: &[DiffField],
+) -> TokenStream2 {
+    let generics = bound::with_similar_bounds(generics, fields);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (similarity, distance) = score_fields(fields, |f| {
+        let member = &f.member;
+        [quote! { &self.#member }, quote! { &other.#member }]
+    });
+    let unused = fields.is_empty().then(|| quote! { let _ = other; });
+
+    quote! {
+        impl #impl_generics ::diffogus::similarity::Similar for #ident #ty_generics #where_clause {
+            fn similarity(&self, other: &Self) -> f64 {
+                #unused
+                #similarity
+            }
+
+            fn distance(&self, other: &Self) -> f64 {
+                #unused
+                #distance
+            }
+        }
+    }
+}
+
+/// `Similar` impl for an enum. Values of the same variant are scored like structs, values of
+/// different variants have nothing in common.
+pub(crate) fn enum_impl(
+    ident: &Ident,
+    generics: &Generics,
+    variants: &[(&Variant, Vec<DiffField>)],
+) -> TokenStream2 {
+    let fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let generics = bound::with_similar_bounds(generics, &fields);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (similarity_arms, distance_arms): (Vec<_>, Vec<_>) = variants
+        .iter()
+        .map(|(variant, fields)| {
+            let variant_ident = &variant.ident;
+            let members: Vec<_> = fields.iter().map(|f| &f.member).collect();
+            let a: Vec<_> = fields.iter().map(|f| f.binding("__sa")).collect();
+            let b: Vec<_> = fields.iter().map(|f| f.binding("__sb")).collect();
+            let (similarity, distance) = score_fields(fields, |f| {
+                let (a, b) = (f.binding("__sa"), f.binding("__sb"));
+                [quote! { #a }, quote! { #b }]
+            });
+            let pattern = quote! {
+                (
+                    Self::#variant_ident { #(#members: #a,)* .. },
+                    Self::#variant_ident { #(#members: #b,)* .. },
+                )
+            };
+            (
+                quote! { #pattern => #similarity },
+                quote! { #pattern => #distance },
+            )
+        })
+        .unzip();
+
+    quote! {
+        impl #impl_generics ::diffogus::similarity::Similar for #ident #ty_generics #where_clause {
+            fn similarity(&self, other: &Self) -> f64 {
+                #[allow(unreachable_patterns)]
+                match (self, other) {
+                    #(#similarity_arms,)*
+                    _ => 0.0,
+                }
+            }
+
+            fn distance(&self, other: &Self) -> f64 {
+                #[allow(unreachable_patterns)]
+                match (self, other) {
+                    #(#distance_arms,)*
+                    _ => 1.0,
+                }
+            }
+        }
+    }
+}
+
+/// `Similar` impl for a type diffed as a whole with `#[diff(atomic)]`.
+pub(crate) fn atomic_impl(ident: &Ident, generics: &Generics) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::diffogus::similarity::Similar for #ident #ty_generics #where_clause {
+            fn similarity(&self, other: &Self) -> f64 {
+                if self == other { 1.0 } else { 0.0 }
+            }
+
+            fn distance(&self, other: &Self) -> f64 {
+                if self == other { 0.0 } else { 1.0 }
+            }
+        }
+    }
+}
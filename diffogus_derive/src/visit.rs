@@ -0,0 +1,97 @@
+//! Generation of `Walkable` impls for derived diff types.
+
+use crate::bound;
+use crate::changed_variant_ident;
+use crate::field::DiffField;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_quote, Generics, Ident, Variant};
+
+/// Statement walking the field diff behind the reference `diff`, under the field's name.
+fn walk_field(field: &DiffField, diff: &TokenStream2) -> TokenStream2 {
+    let walk = field.scoped(
+        "walker",
+        quote! { ::diffogus::visit::Walkable::walk_with(#diff, walker) },
+    );
+    quote! { #walk; }
+}
+
+/// `Walkable` impl for a generated diff struct, walking every field diff.
+pub(crate) fn struct_impl(
+    struct_name: &Ident,
+    generics: &Generics,
+    fields: &[DiffField],
+) -> TokenStream2 {
+    let generics =
+        bound::with_repr_bounds(generics, fields, &quote! { ::diffogus::visit::Walkable });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let walks = fields.iter().map(|f| {
+        let diff_member = &f.diff_member;
+        walk_field(f, &quote! { &self.#diff_member })
+    });
+    let unused = fields.is_empty().then(|| quote! { let _ = walker; });
+
+    quote! {
+        impl #impl_generics ::diffogus::visit::Walkable for #struct_name #ty_generics #where_clause {
+            fn walk_with(&self, walker: &mut ::diffogus::visit::Walker<'_>) {
+                #unused
+                #(#walks)*
+            }
+        }
+    }
+}
+
+/// `Walkable` impl for a generated diff enum. Changes to the fields of a variant are walked one by
+/// one, while a changed variant is reported as a whole.
+pub(crate) fn enum_impl(
+    ident: &Ident,
+    enum_name: &Ident,
+    generics: &Generics,
+    variants: &[(&Variant, Vec<DiffField>)],
+) -> TokenStream2 {
+    let fields: Vec<_> = variants
+        .iter()
+        .flat_map(|(_, fields)| fields.iter().cloned())
+        .collect();
+    let mut generics =
+        bound::with_repr_bounds(generics, &fields, &quote! { ::diffogus::visit::Walkable });
+    if !generics.params.is_empty() {
+        let (_, ty_generics, _) = generics.split_for_impl();
+        let predicate = parse_quote! { #ident #ty_generics: ::core::fmt::Debug };
+        generics.make_where_clause().predicates.push(predicate);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let changed_arms = variants
+        .iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(variant, fields)| {
+            let changed_ident = changed_variant_ident(variant);
+            let diff_members: Vec<_> = fields.iter().map(|f| &f.diff_member).collect();
+            let d: Vec<_> = fields.iter().map(|f| f.binding("__d")).collect();
+            let walks = fields
+                .iter()
+                .zip(&d)
+                .map(|(f, d)| walk_field(f, &quote! { #d }));
+
+            quote! {
+                Self::#changed_ident { #(#diff_members: #d),* } => {
+                    #(#walks)*
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics ::diffogus::visit::Walkable for #enum_name #ty_generics #where_clause {
+            fn walk_with(&self, walker: &mut ::diffogus::visit::Walker<'_>) {
+                match self {
+                    Self::Unchanged => walker.report_unchanged(),
+                    Self::VariantChanged { old, new } => {
+                        walker.report(::diffogus::visit::Change::Changed { old, new })
+                    }
+                    #(#changed_arms)*
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use diffogus::Diff;
+
+#[derive(Debug, Clone, PartialEq, Diff)]
+#[diff(atomic, name = "ColorChanges")]
+struct Color(u8, u8, u8);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+#[diff(bound = "T Diffable")]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
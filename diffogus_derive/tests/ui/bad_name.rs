@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+#[diff(name = "User Changes")]
+struct User {
+    name: String,
+}
+
+fn main() {}
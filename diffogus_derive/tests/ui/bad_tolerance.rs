@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+struct Reading {
+    #[diff(tolerance)]
+    value: f64,
+}
+
+fn main() {}
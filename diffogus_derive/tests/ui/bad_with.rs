@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+struct User {
+    #[diff(with = "not a path")]
+    name: String,
+}
+
+fn main() {}
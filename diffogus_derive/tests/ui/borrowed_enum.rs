@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Debug, Clone, Diff)]
+enum Cell<'a> {
+    Text(&'a str),
+    Empty,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use diffogus::Diff;
+
+#[derive(Debug, Clone, Diff)]
+#[diff(invert, compose)]
+enum Shape {
+    Circle(u32),
+    Empty,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+#[diff(name = "UserChanges")]
+#[diff(name = "UserDiff")]
+struct User {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+enum Never {}
+
+fn main() {}
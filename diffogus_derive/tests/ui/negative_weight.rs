@@ -0,0 +1,10 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+#[diff(similarity)]
+struct User {
+    #[diff(weight = -1.0)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+struct User {
+    #[diff(skip, weight = 2.0)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+struct User {
+    #[diff(skip, with = "name_diff")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+struct Reading {
+    #[diff(atomic, tolerance = 0.1)]
+    value: f64,
+}
+
+fn main() {}
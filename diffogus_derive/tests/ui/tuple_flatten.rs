@@ -0,0 +1,10 @@
+use diffogus::Diff;
+
+struct Inner {
+    name: String,
+}
+
+#[derive(Diff)]
+struct Outer(#[diff(flatten)] Inner);
+
+fn main() {}
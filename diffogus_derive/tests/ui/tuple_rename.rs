@@ -0,0 +1,6 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+struct Point(#[diff(rename = "x")] i32, i32);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
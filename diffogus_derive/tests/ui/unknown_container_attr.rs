@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+#[diff(nmae = "UserChanges")]
+struct User {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Diff)]
+struct User {
+    #[diff(skipp)]
+    name: String,
+}
+
+fn main() {}
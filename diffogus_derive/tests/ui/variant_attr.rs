@@ -0,0 +1,10 @@
+use diffogus::Diff;
+
+#[derive(Debug, Clone, Diff)]
+enum Shape {
+    #[diff(skip)]
+    Empty,
+    Circle(u32),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use diffogus::Diff;
+
+#[derive(Debug, Clone, Diff)]
+enum Choice {
+    Variant(u32),
+    Other,
+}
+
+fn main() {}